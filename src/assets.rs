@@ -1,18 +1,43 @@
 use ggez::graphics;
 use ggez::{Context, GameResult};
 
+use crate::species::Species;
+
 pub struct Assets {
     pub bird: graphics::Image,
     pub obstacle: graphics::Image,
+    pub predator: graphics::Image,
+    /// Sprite of each species, indexed like `Simulation::species`.
+    pub species: Vec<graphics::Image>,
 }
 
 impl Assets {
     pub fn new(ctx: &mut Context) -> GameResult<Assets> {
         let bird = graphics::Image::new(ctx, "/bird.png")?;
         let obstacle = graphics::Image::new(ctx, "/obstacle.png")?;
+        let predator = graphics::Image::new(ctx, "/predator.png")?;
         Ok(Assets {
             bird,
-            obstacle
+            obstacle,
+            predator,
+            species: Vec::new()
         })
     }
-}
\ No newline at end of file
+
+    /// Loads the sprite of every species, using the bird sprite for those without one.
+    pub fn load_species(&mut self, ctx: &mut Context, species: &[Species]) -> GameResult<()> {
+        let mut sprites = Vec::with_capacity(species.len());
+        for species in species.iter() {
+            match &species.sprite {
+                Some(sprite) => sprites.push(graphics::Image::new(ctx, sprite)?),
+                None => sprites.push(self.bird.clone())
+            }
+        }
+        self.species = sprites;
+        Ok(())
+    }
+
+    pub fn species_sprite(&self, species: usize) -> &graphics::Image {
+        self.species.get(species).unwrap_or(&self.bird)
+    }
+}
@@ -0,0 +1,89 @@
+use ggez::graphics;
+use ggez::nalgebra::{Point2, Vector2};
+
+/// Maps between screen coordinates, in pixels from the top left of the
+/// window, and world coordinates of the simulation.
+///
+/// `center` is the world point shown in the middle of the window, and `zoom`
+/// the number of pixels per world unit.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Camera {
+    pub center: Point2<f32>,
+    pub zoom: f32,
+    screen_width: f32,
+    screen_height: f32,
+}
+
+impl Camera {
+    pub const MIN_ZOOM: f32 = 0.1;
+    pub const MAX_ZOOM: f32 = 10.0;
+
+    /// A camera for a window of the given size showing the world at zoom 1,
+    /// with the world origin in the top left corner.
+    pub fn new(screen_width: f32, screen_height: f32) -> Self {
+        Camera {
+            center: Point2::new(screen_width / 2.0, screen_height / 2.0),
+            zoom: 1.0,
+            screen_width,
+            screen_height,
+        }
+    }
+
+    /// A camera for a window of the given size showing `center` in its
+    /// middle at zoom 1.
+    pub fn looking_at(screen_width: f32, screen_height: f32, center: Point2<f32>) -> Self {
+        Camera {
+            center,
+            ..Camera::new(screen_width, screen_height)
+        }
+    }
+
+    pub fn screen_size(&self) -> (f32, f32) {
+        (self.screen_width, self.screen_height)
+    }
+
+    /// Follows a resized window, keeping the same world point in its middle.
+    pub fn resize(&mut self, screen_width: f32, screen_height: f32) {
+        self.screen_width = screen_width;
+        self.screen_height = screen_height;
+    }
+
+    pub fn screen_to_world(&self, point: Point2<f32>) -> Point2<f32> {
+        Point2::new(
+            self.center.x + (point.x - self.screen_width / 2.0) / self.zoom,
+            self.center.y + (point.y - self.screen_height / 2.0) / self.zoom)
+    }
+
+    pub fn world_to_screen(&self, point: Point2<f32>) -> Point2<f32> {
+        Point2::new(
+            (point.x - self.center.x) * self.zoom + self.screen_width / 2.0,
+            (point.y - self.center.y) * self.zoom + self.screen_height / 2.0)
+    }
+
+    /// Moves the view so the world follows a mouse dragged by `delta` pixels.
+    pub fn pan(&mut self, delta: Vector2<f32>) {
+        self.center -= delta / self.zoom;
+    }
+
+    /// Multiplies the zoom by `factor`, within `MIN_ZOOM..=MAX_ZOOM`, keeping
+    /// the world point under the screen point `anchor` where it is.
+    pub fn zoom_at(&mut self, anchor: Point2<f32>, factor: f32) {
+        let world_anchor = self.screen_to_world(anchor);
+        self.zoom = (self.zoom * factor).clamp(Camera::MIN_ZOOM, Camera::MAX_ZOOM);
+        let moved = self.world_to_screen(world_anchor);
+        self.pan(anchor - moved);
+    }
+
+    /// The part of the world in view, for `graphics::set_screen_coordinates`
+    /// before drawing the world.
+    pub fn world_rect(&self) -> graphics::Rect {
+        let top_left = self.screen_to_world(Point2::new(0.0, 0.0));
+        graphics::Rect::new(top_left.x, top_left.y, self.screen_width / self.zoom, self.screen_height / self.zoom)
+    }
+
+    /// The whole window in pixels, for `graphics::set_screen_coordinates`
+    /// before drawing the HUD.
+    pub fn screen_rect(&self) -> graphics::Rect {
+        graphics::Rect::new(0.0, 0.0, self.screen_width, self.screen_height)
+    }
+}
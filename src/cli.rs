@@ -0,0 +1,258 @@
+use structopt::StructOpt;
+
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::PathBuf;
+
+use crate::config::{Config, ConfigError};
+use crate::metrics::MetricsLog;
+use crate::scene::{Scene, SceneError, WorldSize};
+use crate::snapshot::{Snapshot, SnapshotError};
+use crate::trajectory::TrajectoryRecorder;
+use crate::simulation::{Boundary, Neighbourhood, Simulation, SpawnPattern};
+
+/// Command-line options of the `boids` binary.
+#[derive(Debug, Clone, PartialEq, StructOpt)]
+#[structopt(name = "boids", about = "Flocking simulation")]
+pub struct Options {
+    /// Window width, overrides `window.width` from the config file
+    #[structopt(long)]
+    pub width: Option<f32>,
+
+    /// Window height, overrides `window.height` from the config file
+    #[structopt(long)]
+    pub height: Option<f32>,
+
+    /// World width, overrides `world.width` from the config file
+    #[structopt(long)]
+    pub world_width: Option<f32>,
+
+    /// World height, overrides `world.height` from the config file
+    #[structopt(long)]
+    pub world_height: Option<f32>,
+
+    /// Run in desktop fullscreen
+    #[structopt(long, conflicts_with = "windowed")]
+    pub fullscreen: bool,
+
+    /// Run in a normal window
+    #[structopt(long)]
+    pub windowed: bool,
+
+    /// Number of birds to start with
+    #[structopt(long, default_value = "1")]
+    pub birds: usize,
+
+    /// Initial layout of the birds: center, random, grid or circle
+    #[structopt(long, default_value = "center")]
+    pub spawn: SpawnPattern,
+
+    /// Behaviour at the world edges: wrap, bounce, contain or kill.
+    /// Overrides `parameters.boundary` from the config file
+    #[structopt(long)]
+    pub boundary: Option<Boundary>,
+
+    /// Which neighbours the flocking rules see: metric or topological.
+    /// Overrides `parameters.neighbourhood` from the config file
+    #[structopt(long)]
+    pub neighbourhood: Option<Neighbourhood>,
+
+    /// Number of nearest neighbours seen in topological mode.
+    /// Overrides `parameters.topological_neighbours` from the config file
+    #[structopt(long)]
+    pub neighbours: Option<usize>,
+
+    /// Seed for the random number generator, random if not given
+    #[structopt(long, env = "BOIDS_SEED")]
+    pub seed: Option<u64>,
+
+    /// Snapshot file to start from instead of a new flock
+    #[structopt(long, parse(from_os_str))]
+    pub resume: Option<PathBuf>,
+
+    /// Snapshot file written with F5 and read with F9
+    #[structopt(long, parse(from_os_str), default_value = "snapshot.json")]
+    pub snapshot: PathBuf,
+
+    /// Scene file with the world size, obstacles and spawn regions to start
+    /// with. Ignored with `--resume`
+    #[structopt(long, parse(from_os_str))]
+    pub scene: Option<PathBuf>,
+
+    /// Config file with the simulation parameters
+    #[structopt(long, env = "BOIDS_CONFIG", parse(from_os_str))]
+    pub config: Option<PathBuf>,
+
+    /// Run without a window and exit after `--steps` ticks
+    #[structopt(long)]
+    pub headless: bool,
+
+    /// Number of ticks to simulate in headless mode
+    #[structopt(long, default_value = "1000")]
+    pub steps: u64,
+
+    /// CSV file to log the flock metrics to, windowed or headless
+    #[structopt(long, parse(from_os_str))]
+    pub metrics: Option<PathBuf>,
+
+    /// Log the metrics every this many ticks
+    #[structopt(long, default_value = "10")]
+    pub metrics_interval: u64,
+
+    /// File to record every bird's trajectory to each tick, as CSV if it ends
+    /// in `.csv` and in the compact binary format otherwise
+    #[structopt(long, parse(from_os_str))]
+    pub trajectory: Option<PathBuf>,
+}
+
+impl Options {
+    /// The config file to load and watch: `--config`, or `boids.toml`.
+    pub fn config_path(&self) -> PathBuf {
+        self.config.clone().unwrap_or_else(|| PathBuf::from(Config::DEFAULT_PATH))
+    }
+
+    /// The scene file to load and to save with F6: `--scene`, or `scene.toml`.
+    pub fn scene_path(&self) -> PathBuf {
+        self.scene.clone().unwrap_or_else(|| PathBuf::from(Scene::DEFAULT_PATH))
+    }
+
+    /// Loads the `--scene` file, if one was given.
+    pub fn load_scene(&self) -> Result<Option<Scene>, SceneError> {
+        match &self.scene {
+            Some(path) => Scene::load(path).map(Some),
+            None => Ok(None)
+        }
+    }
+
+    /// Loads the config file and applies the window, world, boundary and
+    /// neighbourhood overrides.
+    ///
+    /// A missing default file gives the built-in defaults, while a missing
+    /// file named with `--config` is an error.
+    pub fn load_config(&self) -> Result<Config, ConfigError> {
+        let path = self.config_path();
+        let mut config = if self.config.is_none() && !path.exists() {
+            Config::default()
+        }
+        else {
+            Config::load(&path)?
+        };
+        self.apply_overrides(&mut config);
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Applies the window, world, boundary and neighbourhood overrides to
+    /// `config`, without validating it.
+    pub fn apply_overrides(&self, config: &mut Config) {
+        if let Some(width) = self.width {
+            config.window.width = width;
+        }
+        if let Some(height) = self.height {
+            config.window.height = height;
+        }
+        if let Some(width) = self.world_width {
+            config.world.width = Some(width);
+        }
+        if let Some(height) = self.world_height {
+            config.world.height = Some(height);
+        }
+        if self.fullscreen {
+            config.window.fullscreen = true;
+        }
+        if self.windowed {
+            config.window.fullscreen = false;
+        }
+        if let Some(boundary) = self.boundary {
+            config.parameters.boundary = boundary;
+        }
+        if let Some(neighbourhood) = self.neighbourhood {
+            config.parameters.neighbourhood = neighbourhood;
+        }
+        if let Some(neighbours) = self.neighbours {
+            config.parameters.topological_neighbours = neighbours;
+        }
+    }
+
+    /// Creates the `--metrics` log, if one was asked for.
+    pub fn metrics_log(&self) -> io::Result<Option<MetricsLog<BufWriter<File>>>> {
+        match &self.metrics {
+            Some(path) => MetricsLog::create(path, self.metrics_interval).map(Some),
+            None => Ok(None)
+        }
+    }
+
+    /// Creates the `--trajectory` recorder, if one was asked for.
+    pub fn trajectory_recorder(&self) -> io::Result<Option<TrajectoryRecorder<BufWriter<File>>>> {
+        match &self.trajectory {
+            Some(path) => TrajectoryRecorder::create(path).map(Some),
+            None => Ok(None)
+        }
+    }
+
+    /// The simulation to start with: the `--resume` snapshot if given, which
+    /// ignores `config` and `world`, or else a new one from `simulation`.
+    pub fn initial_simulation(&self, config: &Config, world: Option<WorldSize>) -> Result<Simulation, SnapshotError> {
+        match &self.resume {
+            Some(path) => Simulation::from_snapshot(Snapshot::load(path)?),
+            None => Ok(self.simulation(config, world))
+        }
+    }
+
+    /// Builds the simulation described by `config` and these options, in a
+    /// world of size `world` if given, such as a scene's, so the flock is
+    /// spread over the world it will fly in.
+    pub fn simulation(&self, config: &Config, world: Option<WorldSize>) -> Simulation {
+        let (width, height) = match world {
+            Some(world) => (world.width, world.height),
+            None => config.world_size()
+        };
+        let mut simulation = match self.seed {
+            Some(seed) => Simulation::with_seed(width, height, seed),
+            None => Simulation::new(width, height)
+        };
+        simulation.parameters = config.parameters;
+        simulation.set_species(config.species());
+        simulation.set_rules(&config.rules);
+        simulation.spawn_flock(self.birds, self.spawn);
+        simulation
+    }
+}
+
+/// The output files asked for on the command line.
+pub struct Recorders {
+    pub metrics_log: Option<MetricsLog<BufWriter<File>>>,
+    pub trajectory_recorder: Option<TrajectoryRecorder<BufWriter<File>>>
+}
+
+impl Recorders {
+    pub fn create(options: &Options) -> io::Result<Recorders> {
+        Ok(Recorders {
+            metrics_log: options.metrics_log()?,
+            trajectory_recorder: options.trajectory_recorder()?
+        })
+    }
+
+    pub fn record(&mut self, simulation: &Simulation) -> io::Result<()> {
+        if let Some(metrics_log) = &mut self.metrics_log {
+            metrics_log.record(simulation)?;
+        }
+        if let Some(trajectory_recorder) = &mut self.trajectory_recorder {
+            trajectory_recorder.record(simulation)?;
+        }
+        Ok(())
+    }
+
+    /// Closes the files and starts them over, so the next `record` writes a
+    /// new header. Needed when the simulation is replaced, as its rules may
+    /// differ from those in the headers. Nothing is recorded any more if a
+    /// file cannot be written.
+    pub fn restart(&mut self, options: &Options) -> io::Result<()> {
+        self.metrics_log = None;
+        if let Some(trajectory_recorder) = self.trajectory_recorder.take() {
+            trajectory_recorder.finish()?;
+        }
+        *self = Recorders::create(options)?;
+        Ok(())
+    }
+}
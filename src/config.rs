@@ -0,0 +1,338 @@
+use serde::Deserialize;
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::rules::Rules;
+use crate::simulation::{Parameters, Simulation};
+use crate::species::{Species, SpeciesConfig};
+
+/// Startup settings read from a TOML file.
+///
+/// Every section and key is optional and falls back to the built-in defaults:
+///
+/// ```toml
+/// [window]
+/// width = 1920.0
+/// height = 1080.0
+/// fullscreen = true
+///
+/// [world]
+/// width = 3840.0
+/// height = 2160.0
+///
+/// [parameters]
+/// max_speed = 3.5
+/// alignment_modifier = 1.6
+///
+/// [rules]
+/// order = ["separation", "alignment", "cohesion"]
+/// random_movement = false
+///
+/// [[species]]
+/// name = "starling"
+///
+/// [[species]]
+/// name = "sparrow"
+/// color = [255, 180, 120]
+/// interactions = { starling = "avoid" }
+/// ```
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub window: WindowConfig,
+    pub world: WorldConfig,
+    pub parameters: Parameters,
+    pub rules: Rules,
+    pub species: Vec<SpeciesConfig>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WindowConfig {
+    pub width: f32,
+    pub height: f32,
+    pub fullscreen: bool,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        WindowConfig {
+            width: 1920.0,
+            height: 1080.0,
+            fullscreen: true,
+        }
+    }
+}
+
+/// Size of the world the birds fly in. Each side not given is the same as the
+/// window's, so by default the whole world fits in the window.
+#[derive(Debug, Copy, Clone, PartialEq, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WorldConfig {
+    pub width: Option<f32>,
+    pub height: Option<f32>,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, io::Error),
+    Parse(toml::de::Error),
+    /// A value parsed but is out of range. `key` is the dotted path, e.g. `parameters.max_speed`.
+    Invalid { key: String, message: String },
+    /// A reloaded config could not be merged with the values in use, see `Config::reapply`.
+    Reapply(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, e) => write!(f, "cannot read {}: {}", path.display(), e),
+            ConfigError::Parse(e) => write!(f, "{}", e),
+            ConfigError::Invalid { key, message } => write!(f, "invalid value for key `{}`: {}", key, message),
+            ConfigError::Reapply(message) => write!(f, "cannot apply the reloaded parameters: {}", message),
+        }
+    }
+}
+
+impl Error for ConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ConfigError::Io(_, e) => Some(e),
+            ConfigError::Parse(e) => Some(e),
+            ConfigError::Invalid { .. } | ConfigError::Reapply(_) => None,
+        }
+    }
+}
+
+impl Config {
+    pub const DEFAULT_PATH: &'static str = "boids.toml";
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Config, ConfigError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|e| ConfigError::Io(path.to_path_buf(), e))?;
+        Config::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<Config, ConfigError> {
+        let config: Config = toml::from_str(text).map_err(ConfigError::Parse)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        positive("window.width", self.window.width)?;
+        positive("window.height", self.window.height)?;
+        if let Some(width) = self.world.width {
+            positive("world.width", width)?;
+        }
+        if let Some(height) = self.world.height {
+            positive("world.height", height)?;
+        }
+
+        let parameters = &self.parameters;
+        validate_parameters("parameters", parameters)?;
+
+        for (i, name) in self.rules.order.iter().enumerate() {
+            if Rules::builtin(name).is_none() {
+                return Err(ConfigError::Invalid {
+                    key: "rules.order".to_string(),
+                    message: format!("unknown rule `{}`, expected one of {}", name, Rules::NAMES.join(", "))
+                });
+            }
+            if self.rules.order[..i].contains(name) {
+                return Err(ConfigError::Invalid { key: "rules.order".to_string(), message: format!("rule `{}` is listed twice", name) });
+            }
+        }
+
+        let names: Vec<&str> = self.species.iter().map(|species| species.name.as_str()).collect();
+        for (i, species) in self.species.iter().enumerate() {
+            if names[..i].contains(&species.name.as_str()) {
+                return Err(ConfigError::Invalid { key: "species.name".to_string(), message: format!("species `{}` is listed twice", species.name) });
+            }
+            for other in species.interactions.keys() {
+                if !names.contains(&other.as_str()) {
+                    return Err(ConfigError::Invalid {
+                        key: format!("species.{}.interactions", species.name),
+                        message: format!("unknown species `{}`", other)
+                    });
+                }
+            }
+            validate_bird_parameters(&format!("species.{}.parameters", species.name), &species.parameters.apply(parameters))?;
+        }
+        Ok(())
+    }
+
+    /// Applies the parameters and species of this config to `simulation`,
+    /// which was set up from `previous`, a config with as many species.
+    ///
+    /// Only keys whose value differs from `previous` are copied, so values
+    /// changed while running, with a key or on the parameter panel, survive
+    /// edits to other keys of the file. On error `simulation` is left as it was.
+    pub fn reapply(&self, previous: &Config, simulation: &mut Simulation) -> Result<(), ConfigError> {
+        let parameters = apply_changed(&simulation.parameters, &previous.parameters, &self.parameters)?;
+
+        let mut species = self.species();
+        for ((next, previous), current) in species.iter_mut().zip(previous.species()).zip(simulation.species.iter()) {
+            if let (Some(next), Some(previous), Some(current)) = (next.parameters.as_mut(), previous.parameters, current.parameters) {
+                *next = apply_changed(&current, &previous, next)?;
+            }
+        }
+        simulation.parameters = parameters;
+        simulation.set_species(species);
+        Ok(())
+    }
+
+    /// Width and height of the world, falling back to the window's.
+    pub fn world_size(&self) -> (f32, f32) {
+        (self.world.width.unwrap_or(self.window.width), self.world.height.unwrap_or(self.window.height))
+    }
+
+    /// The species of the flock, or a single default species if none are configured.
+    pub fn species(&self) -> Vec<Species> {
+        if self.species.is_empty() {
+            return vec![Species::default()];
+        }
+        let names: Vec<&str> = self.species.iter().map(|species| species.name.as_str()).collect();
+        self.species.iter().map(|species| species.build(&self.parameters, &names)).collect()
+    }
+}
+
+/// `current` with every key of `next` that differs from `previous` copied over.
+fn apply_changed(current: &Parameters, previous: &Parameters, next: &Parameters) -> Result<Parameters, ConfigError> {
+    let table = |parameters: &Parameters| match toml::Value::try_from(parameters) {
+        Ok(toml::Value::Table(table)) => Ok(table),
+        Ok(other) => Err(ConfigError::Reapply(format!("expected a table of parameters, got a {}", other.type_str()))),
+        Err(e) => Err(ConfigError::Reapply(e.to_string()))
+    };
+    let (previous, next) = (table(previous)?, table(next)?);
+    let mut merged = table(current)?;
+    for (key, value) in next {
+        if previous.get(&key) != Some(&value) {
+            merged.insert(key, value);
+        }
+    }
+    toml::Value::Table(merged).try_into().map_err(|e: toml::de::Error| ConfigError::Reapply(e.to_string()))
+}
+
+/// Checks every key of `parameters` like `Config::validate` checks the
+/// `[parameters]` section, naming them `<section>.<key>` in errors.
+pub fn validate_parameters(section: &str, parameters: &Parameters) -> Result<(), ConfigError> {
+    let key = |name: &str| format!("{}.{}", section, name);
+    validate_bird_parameters(section, parameters)?;
+    positive(&key("boundary_margin"), parameters.boundary_margin)?;
+    non_negative(&key("boundary_modifier"), parameters.boundary_modifier)?;
+    if parameters.topological_neighbours == 0 {
+        return Err(ConfigError::Invalid { key: key("topological_neighbours"), message: "expected at least one neighbour".to_string() });
+    }
+    positive(&key("predator_view_distance"), parameters.predator_view_distance)?;
+    non_negative(&key("predator_kill_radius"), parameters.predator_kill_radius)?;
+    positive(&key("predator_max_speed"), parameters.predator_max_speed)?;
+    non_negative(&key("predator_max_steering_velocity"), parameters.predator_max_steering_velocity)?;
+    Ok(())
+}
+
+/// Checks the per-bird keys of `parameters`, naming them `<section>.<key>` in errors.
+fn validate_bird_parameters(section: &str, parameters: &Parameters) -> Result<(), ConfigError> {
+    let key = |name: &str| format!("{}.{}", section, name);
+    positive(&key("alignment_view_distance"), parameters.alignment_view_distance)?;
+    positive(&key("separation_view_distance"), parameters.separation_view_distance)?;
+    positive(&key("cohesion_view_distance"), parameters.cohesion_view_distance)?;
+    positive(&key("obstacle_radius"), parameters.obstacle_radius)?;
+    angle(&key("alignment_field_of_view"), parameters.alignment_field_of_view)?;
+    angle(&key("separation_field_of_view"), parameters.separation_field_of_view)?;
+    angle(&key("cohesion_field_of_view"), parameters.cohesion_field_of_view)?;
+    angle(&key("obstacle_field_of_view"), parameters.obstacle_field_of_view)?;
+    angle(&key("flee_field_of_view"), parameters.flee_field_of_view)?;
+    positive(&key("max_speed"), parameters.max_speed)?;
+    non_negative(&key("max_steering_velocity"), parameters.max_steering_velocity)?;
+    non_negative(&key("random_movement"), parameters.random_movement)?;
+    non_negative(&key("alignment_modifier"), parameters.alignment_modifier)?;
+    non_negative(&key("separation_modifier"), parameters.separation_modifier)?;
+    non_negative(&key("cohesion_modifier"), parameters.cohesion_modifier)?;
+    non_negative(&key("obstacle_modifier"), parameters.obstacle_modifier)?;
+    positive(&key("look_ahead_distance"), parameters.look_ahead_distance)?;
+    positive(&key("fear_radius"), parameters.fear_radius)?;
+    non_negative(&key("flee_modifier"), parameters.flee_modifier)?;
+    Ok(())
+}
+
+fn positive(key: &str, value: f32) -> Result<(), ConfigError> {
+    if value.is_finite() && value > 0.0 {
+        Ok(())
+    }
+    else {
+        Err(ConfigError::Invalid { key: key.to_string(), message: format!("expected a positive number, got {}", value) })
+    }
+}
+
+fn non_negative(key: &str, value: f32) -> Result<(), ConfigError> {
+    if value.is_finite() && value >= 0.0 {
+        Ok(())
+    }
+    else {
+        Err(ConfigError::Invalid { key: key.to_string(), message: format!("expected zero or a positive number, got {}", value) })
+    }
+}
+
+fn angle(key: &str, value: f32) -> Result<(), ConfigError> {
+    if value.is_finite() && value > 0.0 && value <= 360.0 {
+        Ok(())
+    }
+    else {
+        Err(ConfigError::Invalid { key: key.to_string(), message: format!("expected an angle above 0 and up to 360 degrees, got {}", value) })
+    }
+}
+
+/// Polls a config file's modification time and reloads it when it changes.
+pub struct ConfigWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    last_check: Instant,
+}
+
+impl ConfigWatcher {
+    pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        let path = path.into();
+        let modified = modified(&path);
+        ConfigWatcher {
+            path,
+            modified,
+            last_check: Instant::now(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the freshly loaded config if the file changed since the last
+    /// call, checking the file at most once per `POLL_INTERVAL`.
+    pub fn poll(&mut self) -> Option<Result<Config, ConfigError>> {
+        if self.last_check.elapsed() < ConfigWatcher::POLL_INTERVAL {
+            return None;
+        }
+        self.last_check = Instant::now();
+        self.check()
+    }
+
+    /// Like `poll`, but without waiting for `POLL_INTERVAL`.
+    pub fn check(&mut self) -> Option<Result<Config, ConfigError>> {
+        let modified = modified(&self.path);
+        if modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+        Some(Config::load(&self.path))
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
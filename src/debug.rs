@@ -18,15 +18,11 @@ pub fn draw_debug_circles(
     Ok(())
 }
 pub fn draw_debug_vectors(
-            alignment_vector: graphics::Mesh,
-            separation_vector: graphics::Mesh,
-            cohesion_vector: graphics::Mesh,
-            obstacle_vector: graphics::Mesh,
+            steering_vectors: Vec<graphics::Mesh>,
             ctx: &mut Context
         ) -> GameResult<()> {
-    graphics::draw(ctx, &alignment_vector, graphics::DrawParam::default())?;
-    graphics::draw(ctx, &separation_vector, graphics::DrawParam::default())?;
-    graphics::draw(ctx, &cohesion_vector, graphics::DrawParam::default())?;
-    graphics::draw(ctx, &obstacle_vector, graphics::DrawParam::default())?;
+    for steering_vector in steering_vectors.iter() {
+        graphics::draw(ctx, steering_vector, graphics::DrawParam::default())?;
+    }
     Ok(())
 }
\ No newline at end of file
@@ -2,20 +2,29 @@ use ggez::{Context, GameResult};
 use ggez::graphics;
 use ggez::graphics::{Mesh, MeshBuilder, DrawMode};
 use ggez::nalgebra::{Point2, Vector2};
+use serde::{Deserialize, Serialize};
 
 use crate::assets::Assets;
+use crate::simulation::Boundary;
+use crate::snapshot::{point, points, vector, vectors};
+use crate::species::Species;
 use crate::tools::Tools;
 
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Bird {
+    /// Identifies the bird across ticks, as its index changes when others die.
+    /// Given out by `Simulation::add_bird`; 0 for birds made with `new` alone.
+    pub id: u64,
+    #[serde(with = "point")]
     pub pos: Point2<f32>,
+    #[serde(with = "vector")]
     pub vel: Vector2<f32>,
-    pub align: Vector2<f32>,
-    pub sep: Vector2<f32>,
-    pub coh: Vector2<f32>,
-    pub obst: Vector2<f32>,
-    pub random: Vector2<f32>,
+    /// Steering from the last update, one vector per rule in the simulation's rule order.
+    #[serde(with = "vectors")]
+    pub steering: Vec<Vector2<f32>>,
+    /// Index into the simulation's species list.
+    pub species: usize,
     pub is_alive: bool
 }
 
@@ -24,27 +33,21 @@ impl Bird{
 
     pub fn new(pos: Point2<f32>, vel: Vector2<f32>) -> Self {
         Bird{
+            id: 0,
             pos: pos,
             vel: vel,
-            align: Vector2::new(0.0, 0.0),
-            sep: Vector2::new(0.0, 0.0),
-            coh: Vector2::new(0.0, 0.0),
-            obst: Vector2::new(0.0, 0.0),
-            random: Vector2::new(0.0, 0.0),
+            steering: Vec::new(),
+            species: 0,
             is_alive: true
         }
     }
 
-    pub fn update(&mut self, align: Vector2<f32>, sep: Vector2<f32>, coh: Vector2<f32>, random: Vector2<f32>, obst: Vector2<f32>, max_velocity: f32, screen_width: f32, screen_height: f32) {
+    pub fn update(&mut self, steering: Vec<Vector2<f32>>, max_velocity: f32, boundary: Boundary, screen_width: f32, screen_height: f32) {
         // update vectors in self
-        self.align = align;
-        self.sep = sep;
-        self.coh = coh;
-        self.random = random;
-        self.obst = obst;
+        self.steering = steering;
 
         // update velocity
-        let acceleration: Vector2<f32> = align + sep + coh + random + obst;
+        let acceleration: Vector2<f32> = self.steering.iter().fold(Vector2::new(0.0, 0.0), |sum, vector| sum + vector);
         if acceleration.x == 0.0 && acceleration.y == 0.0 {
             self.vel *= Bird::SELF_ACCELERATION;
             Tools::limit_vector(&mut self.vel, max_velocity);
@@ -57,45 +60,48 @@ impl Bird{
         // update position
         self.pos.x += self.vel.x;
         self.pos.y += self.vel.y;
-        if self.pos.x < 0.0 {
-            self.pos.x += screen_width;
-        }
-        else if self.pos.x > screen_width {
-            self.pos.x -= screen_width;
-        }
-        if self.pos.y < 0.0 {
-            self.pos.y += screen_height;
-        }
-        else if self.pos.y > screen_height {
-            self.pos.y -= screen_height;
+        if !boundary.apply(&mut self.pos, &mut self.vel, screen_width, screen_height) {
+            self.is_alive = false;
         }
     }
 
-    pub fn draw(&mut self, ctx: &mut Context, assets: &Assets) -> GameResult<()> {
+    pub fn draw(&mut self, ctx: &mut Context, assets: &Assets, species: &Species) -> GameResult<()> {
         let drawparams = graphics::DrawParam::new()
                                 .dest(self.pos)
                                 .scale(Vector2::new(0.05, 0.05))
                                 .offset(Point2::new(0.47, 0.7))
-                                .rotation((self.vel.y).atan2(self.vel.x) + 3.1415/2.0);
-        graphics::draw(ctx, &assets.bird, drawparams)
+                                .rotation((self.vel.y).atan2(self.vel.x) + std::f32::consts::FRAC_PI_2)
+                                .color(species.color.into());
+        graphics::draw(ctx, assets.species_sprite(self.species), drawparams)
     }
 
 
-    pub fn alignment_view_distance_circle(&self, ctx: &mut Context, alignment_view_distance: f32) -> graphics::Mesh {
-        MeshBuilder::new().circle(
-            graphics::DrawMode::stroke(1.0), 
-            Point2::new(self.pos.x, self.pos.y), 
-            alignment_view_distance, 
-            1.0, 
-            (255, 0, 0).into()).build(ctx).unwrap()
+    pub fn alignment_view_distance_circle(&self, ctx: &mut Context, alignment_view_distance: f32, field_of_view: f32) -> graphics::Mesh {
+        self.view_cone(ctx, alignment_view_distance, field_of_view)
     }
-    pub fn separation_view_distance_circle(&self, ctx: &mut Context, separation_view_distance: f32) -> graphics::Mesh {
-        MeshBuilder::new().circle(
-            graphics::DrawMode::stroke(1.0), 
-            Point2::new(self.pos.x, self.pos.y), 
-            separation_view_distance, 
-            1.0, 
-            (255, 0, 0).into()).build(ctx).unwrap()
+    pub fn separation_view_distance_circle(&self, ctx: &mut Context, separation_view_distance: f32, field_of_view: f32) -> graphics::Mesh {
+        self.view_cone(ctx, separation_view_distance, field_of_view)
+    }
+    /// The area seen within `view_distance` and `field_of_view` degrees of the
+    /// heading, a plain circle when nothing is behind the bird's back.
+    fn view_cone(&self, ctx: &mut Context, view_distance: f32, field_of_view: f32) -> graphics::Mesh {
+        if field_of_view >= 360.0 || (self.vel.x == 0.0 && self.vel.y == 0.0) {
+            return MeshBuilder::new().circle(
+                graphics::DrawMode::stroke(1.0), 
+                Point2::new(self.pos.x, self.pos.y), 
+                view_distance, 
+                1.0, 
+                (255, 0, 0).into()).build(ctx).unwrap();
+        }
+        const SEGMENTS: usize = 32;
+        let heading = self.vel.y.atan2(self.vel.x);
+        let half_angle = (field_of_view / 2.0).to_radians();
+        let mut points = vec![self.pos];
+        for i in 0..=SEGMENTS {
+            let angle = heading - half_angle + 2.0 * half_angle * i as f32 / SEGMENTS as f32;
+            points.push(Point2::new(self.pos.x + view_distance * angle.cos(), self.pos.y + view_distance * angle.sin()));
+        }
+        Mesh::new_polygon(ctx, DrawMode::stroke(1.0), &points, (255, 0, 0).into()).unwrap()
     }
     pub fn center_point(&self, ctx: &mut Context) -> graphics::Mesh {
         MeshBuilder::new().circle(
@@ -105,64 +111,367 @@ impl Bird{
             1.0, 
             (255, 0, 0).into()).build(ctx).unwrap()
     }
-    pub fn alignment_vector(&self, ctx: &mut Context) -> graphics::Mesh {
-        if self.align.x == 0.0 && self.align.y == 0.0 {
-            Mesh::new_circle(ctx, DrawMode::fill(), self.pos, 1.0, 1.0, (255, 0, 0).into()).unwrap()
+    pub fn feeler(&self, ctx: &mut Context, dir: Vector2<f32>, length: f32) -> graphics::Mesh {
+        Mesh::new_line(ctx, &[self.pos, self.pos + dir * length], 1.0, (255, 225, 0).into()).unwrap()
+    }
+    pub fn steering_vector(&self, ctx: &mut Context, steering: Vector2<f32>, color: (u8, u8, u8)) -> graphics::Mesh {
+        if steering.x == 0.0 && steering.y == 0.0 {
+            Mesh::new_circle(ctx, DrawMode::fill(), self.pos, 1.0, 1.0, color.into()).unwrap()
         }
         else {
-            Mesh::new_line(ctx, &[self.pos, Point2::new(self.pos.x + self.align.x * 100.0, self.pos.y + self.align.y * 100.0)], 1.0, (255, 0, 0).into()).unwrap()
+            Mesh::new_line(ctx, &[self.pos, Point2::new(self.pos.x + steering.x * 100.0, self.pos.y + steering.y * 100.0)], 1.0, color.into()).unwrap()
         }
     }
-    pub fn separation_vector(&self, ctx: &mut Context) -> graphics::Mesh {
-        if self.sep.x == 0.0 && self.sep.y == 0.0 {
-            Mesh::new_circle(ctx, DrawMode::fill(), self.pos, 1.0, 1.0, (255, 0, 0).into()).unwrap()
-        }
-        else {
-            Mesh::new_line(ctx, &[self.pos, Point2::new(self.pos.x + self.sep.x * 100.0, self.pos.y + self.sep.y * 100.0)], 1.0, (0, 255, 0).into()).unwrap()
+}
+
+/// How an obstacle moves each tick.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Motion {
+    Static,
+    /// Moves by `vel` every tick and bounces off the edges of the world.
+    Drift {
+        #[serde(with = "vector")]
+        vel: Vector2<f32>
+    },
+    /// Circles `center` at `radius`, turning `angular_speed` radians per tick.
+    Orbit {
+        #[serde(with = "point")]
+        center: Point2<f32>,
+        radius: f32,
+        angular_speed: f32,
+        #[serde(default)]
+        angle: f32
+    },
+    /// Travels through `waypoints` at `speed`, starting over after the last one.
+    Path {
+        #[serde(with = "points")]
+        waypoints: Vec<Point2<f32>>,
+        speed: f32,
+        #[serde(default)]
+        next: usize
+    },
+    /// Moves towards the mouse cursor at up to `speed`.
+    FollowMouse { speed: f32 },
+}
+
+/// The outline birds steer around. Coordinates are relative to the obstacle's `pos`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Shape {
+    /// A single point drawn with the obstacle sprite. The original obstacle.
+    Point,
+    /// A line segment from `start` to `end`.
+    Wall {
+        #[serde(with = "vector")]
+        start: Vector2<f32>,
+        #[serde(with = "vector")]
+        end: Vector2<f32>
+    },
+    /// An axis-aligned rectangle centred on `pos`.
+    Rect { half_width: f32, half_height: f32 },
+    /// A closed polygon, convex or concave.
+    Polygon {
+        #[serde(with = "vectors")]
+        vertices: Vec<Vector2<f32>>
+    },
+}
+
+impl Shape {
+    /// A wall between two points, with the position of the obstacle that holds it.
+    pub fn wall(start: Point2<f32>, end: Point2<f32>) -> (Point2<f32>, Shape) {
+        let pos = Point2::new((start.x + end.x) / 2.0, (start.y + end.y) / 2.0);
+        (pos, Shape::Wall { start: start - pos, end: end - pos })
+    }
+
+    /// A rectangle spanning two opposite corners, with the position of the obstacle that holds it.
+    pub fn rect(corner: Point2<f32>, opposite: Point2<f32>) -> (Point2<f32>, Shape) {
+        let pos = Point2::new((corner.x + opposite.x) / 2.0, (corner.y + opposite.y) / 2.0);
+        (pos, Shape::Rect { half_width: (corner.x - opposite.x).abs() / 2.0, half_height: (corner.y - opposite.y).abs() / 2.0 })
+    }
+
+    /// A polygon through `vertices`, with the position of the obstacle that holds it.
+    pub fn polygon(vertices: &[Point2<f32>]) -> (Point2<f32>, Shape) {
+        let mut pos: Point2<f32> = Point2::new(0.0, 0.0);
+        for vertex in vertices.iter() {
+            pos.x += vertex.x / vertices.len() as f32;
+            pos.y += vertex.y / vertices.len() as f32;
         }
+        (pos, Shape::Polygon { vertices: vertices.iter().map(|vertex| vertex - pos).collect() })
     }
-    pub fn cohesion_vector(&self, ctx: &mut Context) -> graphics::Mesh {
-        if self.coh.x == 0.0 && self.coh.y == 0.0 {
-            Mesh::new_circle(ctx, DrawMode::fill(), self.pos, 1.0, 1.0, (255, 0, 0).into()).unwrap()
+
+    /// The corners of the shape placed at `pos`, in order.
+    pub fn vertices(&self, pos: Point2<f32>) -> Vec<Point2<f32>> {
+        match self {
+            Shape::Point => vec![pos],
+            Shape::Wall { start, end } => vec![pos + start, pos + end],
+            Shape::Rect { half_width, half_height } => vec![
+                Point2::new(pos.x - half_width, pos.y - half_height),
+                Point2::new(pos.x + half_width, pos.y - half_height),
+                Point2::new(pos.x + half_width, pos.y + half_height),
+                Point2::new(pos.x - half_width, pos.y + half_height)
+            ],
+            Shape::Polygon { vertices } => vertices.iter().map(|vertex| pos + vertex).collect()
         }
-        else {
-            Mesh::new_line(ctx, &[self.pos, Point2::new(self.pos.x + self.coh.x * 100.0, self.pos.y + self.coh.y * 100.0)], 1.0, (0, 0, 255).into()).unwrap()
+    }
+
+    /// The point on the outline of the shape placed at `pos` that is closest to `point`.
+    pub fn closest_point(&self, pos: Point2<f32>, point: Point2<f32>) -> Point2<f32> {
+        let vertices = self.vertices(pos);
+        match self {
+            Shape::Point => pos,
+            Shape::Wall { .. } => Tools::closest_point_on_segment(vertices[0], vertices[1], point),
+            Shape::Rect { .. } | Shape::Polygon { .. } => {
+                let mut closest = pos;
+                let mut closest_distance = f32::INFINITY;
+                for i in 0..vertices.len() {
+                    let candidate = Tools::closest_point_on_segment(vertices[i], vertices[(i + 1) % vertices.len()], point);
+                    let distance = Tools::vector_length(&(point - candidate));
+                    if distance < closest_distance {
+                        closest = candidate;
+                        closest_distance = distance;
+                    }
+                }
+                closest
+            }
         }
     }
-    pub fn obstacle_vector(&self, ctx: &mut Context) -> graphics::Mesh {
-        if self.obst.x == 0.0 && self.obst.y == 0.0 {
-            Mesh::new_circle(ctx, DrawMode::fill(), self.pos, 1.0, 1.0, (255, 225, 0).into()).unwrap()
+
+    /// The first hit of a ray from `origin` in unit direction `dir` on the shape
+    /// placed at `pos`, as the distance along the ray and the surface normal
+    /// facing the ray. A `Point` is treated as a circle of `point_radius`.
+    pub fn ray_cast(&self, pos: Point2<f32>, origin: Point2<f32>, dir: Vector2<f32>, point_radius: f32) -> Option<(f32, Vector2<f32>)> {
+        if let Shape::Point = self {
+            let distance = Tools::ray_circle_intersection(origin, dir, pos, point_radius)?;
+            let mut normal: Vector2<f32> = (origin + dir * distance) - pos;
+            if Tools::vector_length(&normal) == 0.0 {
+                normal = -dir;
+            }
+            Tools::normalize_vector(&mut normal);
+            return Some((distance, normal));
         }
-        else {
-            Mesh::new_line(ctx, &[self.pos, Point2::new(self.pos.x + self.obst.x * 100.0, self.pos.y + self.obst.y * 100.0)], 1.0, (255, 225, 0).into()).unwrap()
+
+        let vertices = self.vertices(pos);
+        let edges = match self {
+            Shape::Wall { .. } => 1,
+            _ => vertices.len()
+        };
+        let mut hit: Option<(f32, Vector2<f32>)> = None;
+        for i in 0..edges {
+            let start = vertices[i];
+            let end = vertices[(i + 1) % vertices.len()];
+            if let Some(distance) = Tools::ray_segment_intersection(origin, dir, start, end) {
+                if hit.is_none_or(|(closest, _)| distance < closest) {
+                    let mut normal: Vector2<f32> = Vector2::new(start.y - end.y, end.x - start.x);
+                    Tools::normalize_vector(&mut normal);
+                    if normal.dot(&dir) > 0.0 {
+                        normal = -normal;
+                    }
+                    hit = Some((distance, normal));
+                }
+            }
+        }
+        hit
+    }
+
+    /// Whether `point` lies inside the shape placed at `pos`. Points and walls have no inside.
+    pub fn contains(&self, pos: Point2<f32>, point: Point2<f32>) -> bool {
+        match self {
+            Shape::Point | Shape::Wall { .. } => false,
+            Shape::Rect { half_width, half_height } => (point.x - pos.x).abs() < *half_width && (point.y - pos.y).abs() < *half_height,
+            Shape::Polygon { .. } => {
+                // even-odd rule, so concave polygons work too
+                let vertices = self.vertices(pos);
+                let mut inside = false;
+                for i in 0..vertices.len() {
+                    let a = vertices[i];
+                    let b = vertices[(i + 1) % vertices.len()];
+                    if (a.y > point.y) != (b.y > point.y) && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x) {
+                        inside = !inside;
+                    }
+                }
+                inside
+            }
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Obstacle {
+    #[serde(with = "point")]
     pub pos: Point2<f32>,
-    pub radius: f32,
+    /// Distance moved during the last update, see `predicted_pos`.
+    #[serde(with = "vector")]
+    pub vel: Vector2<f32>,
+    /// Distance within which birds evade the obstacle, or `None` for their
+    /// `obstacle_radius` parameter.
+    pub radius: Option<f32>,
+    pub shape: Shape,
+    pub motion: Motion,
     pub is_alive: bool
 }
 
 impl Obstacle{
 
-    pub fn new(pos: Point2<f32>, radius: f32) -> Self {
+    pub fn new(pos: Point2<f32>, radius: Option<f32>) -> Self {
+        Obstacle::with_motion(pos, radius, Motion::Static)
+    }
+
+    pub fn with_motion(pos: Point2<f32>, radius: Option<f32>, motion: Motion) -> Self {
         Obstacle{
             pos: pos,
+            vel: Vector2::new(0.0, 0.0),
             radius: radius,
+            shape: Shape::Point,
+            motion,
+            is_alive: true
+        }
+    }
+
+    pub fn with_shape(pos: Point2<f32>, radius: Option<f32>, shape: Shape) -> Self {
+        Obstacle{
+            shape,
+            ..Obstacle::new(pos, radius)
+        }
+    }
+
+    /// Where the obstacle will be after its next update if it keeps moving
+    /// like in the last one. Birds steer clear of this position rather than
+    /// the current one, so moving obstacles do not run them over.
+    pub fn predicted_pos(&self) -> Point2<f32> {
+        self.pos + self.vel
+    }
+
+    /// The point of the obstacle's outline closest to `point`.
+    pub fn closest_point(&self, point: Point2<f32>) -> Point2<f32> {
+        self.shape.closest_point(self.pos, point)
+    }
+
+    pub fn contains(&self, point: Point2<f32>) -> bool {
+        self.shape.contains(self.pos, point)
+    }
+
+    /// See `Shape::ray_cast`.
+    pub fn ray_cast(&self, origin: Point2<f32>, dir: Vector2<f32>, point_radius: f32) -> Option<(f32, Vector2<f32>)> {
+        self.shape.ray_cast(self.pos, origin, dir, point_radius)
+    }
+
+    /// Moves the obstacle one tick along its `motion`. `mouse` is the cursor
+    /// position for `Motion::FollowMouse`, which stays put without one.
+    pub fn update(&mut self, screen_width: f32, screen_height: f32, mouse: Option<Point2<f32>>) {
+        let old_pos = self.pos;
+        match &mut self.motion {
+            Motion::Static => (),
+            Motion::Drift { vel } => {
+                self.pos += *vel;
+                if (self.pos.x < 0.0 && vel.x < 0.0) || (self.pos.x > screen_width && vel.x > 0.0) {
+                    vel.x = -vel.x;
+                }
+                if (self.pos.y < 0.0 && vel.y < 0.0) || (self.pos.y > screen_height && vel.y > 0.0) {
+                    vel.y = -vel.y;
+                }
+            },
+            Motion::Orbit { center, radius, angular_speed, angle } => {
+                *angle = (*angle + *angular_speed) % (2.0 * std::f32::consts::PI);
+                self.pos = Point2::new(center.x + *radius * angle.cos(), center.y + *radius * angle.sin());
+            },
+            Motion::Path { waypoints, speed, next } => {
+                if !waypoints.is_empty() {
+                    *next %= waypoints.len();
+                    if Obstacle::move_towards(&mut self.pos, waypoints[*next], *speed) {
+                        *next = (*next + 1) % waypoints.len();
+                    }
+                }
+            },
+            Motion::FollowMouse { speed } => {
+                if let Some(mouse) = mouse {
+                    Obstacle::move_towards(&mut self.pos, mouse, *speed);
+                }
+            }
+        }
+        self.vel = self.pos - old_pos;
+    }
+
+    /// Moves `pos` up to `speed` towards `target` and returns whether it arrived.
+    fn move_towards(pos: &mut Point2<f32>, target: Point2<f32>, speed: f32) -> bool {
+        let mut step: Vector2<f32> = Tools::get_vec_from_to(target, *pos);
+        if Tools::vector_length(&step) <= speed {
+            *pos = target;
+            return true;
+        }
+        Tools::limit_vector(&mut step, speed);
+        *pos += step;
+        false
+    }
+
+    pub fn draw(&mut self, ctx: &mut Context, assets: &Assets) -> GameResult<()> {
+        const COLOR: (u8, u8, u8) = (150, 150, 170);
+
+        let vertices = self.shape.vertices(self.pos);
+        match self.shape {
+            Shape::Point => {
+                let drawparams = graphics::DrawParam::new().
+                                            scale(Vector2::new(0.1, 0.1)).
+                                            offset(Point2::new(0.5, 0.5)).
+                                            dest(self.pos);
+                graphics::draw(ctx, &assets.obstacle, drawparams)
+            },
+            // a degenerate shape has no mesh, so there is nothing to draw
+            Shape::Wall { .. } => match Mesh::new_line(ctx, &vertices, 4.0, COLOR.into()) {
+                Ok(wall) => graphics::draw(ctx, &wall, graphics::DrawParam::default()),
+                Err(_) => Ok(())
+            },
+            Shape::Rect { .. } | Shape::Polygon { .. } => match Mesh::new_polygon(ctx, DrawMode::fill(), &vertices, COLOR.into()) {
+                Ok(polygon) => graphics::draw(ctx, &polygon, graphics::DrawParam::default()),
+                Err(_) => Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Predator {
+    #[serde(with = "point")]
+    pub pos: Point2<f32>,
+    #[serde(with = "vector")]
+    pub vel: Vector2<f32>,
+    #[serde(with = "vector")]
+    pub steer: Vector2<f32>,
+    pub is_alive: bool
+}
+
+impl Predator{
+
+    pub fn new(pos: Point2<f32>, vel: Vector2<f32>) -> Self {
+        Predator{
+            pos,
+            vel,
+            steer: Vector2::new(0.0, 0.0),
             is_alive: true
         }
     }
-    pub fn update(&mut self) {
-        todo!()        
+
+    /// Moves the predator. It wraps with `Boundary::Wrap` and bounces off the edges otherwise.
+    pub fn update(&mut self, steer: Vector2<f32>, max_velocity: f32, boundary: Boundary, screen_width: f32, screen_height: f32) {
+        self.steer = steer;
+
+        self.vel += steer;
+        Tools::limit_vector(&mut self.vel, max_velocity);
+
+        self.pos.x += self.vel.x;
+        self.pos.y += self.vel.y;
+        match boundary {
+            Boundary::Wrap => Tools::wrap_position(&mut self.pos, screen_width, screen_height),
+            _ => Tools::bounce_position(&mut self.pos, &mut self.vel, screen_width, screen_height)
+        }
     }
+
     pub fn draw(&mut self, ctx: &mut Context, assets: &Assets) -> GameResult<()> {
-        let drawparams = graphics::DrawParam::new().
-                                    scale(Vector2::new(0.1, 0.1)).
-                                    offset(Point2::new(0.5, 0.5)).
-                                    dest(self.pos);
-        graphics::draw(ctx, &assets.obstacle, drawparams)
+        let drawparams = graphics::DrawParam::new()
+                                .dest(self.pos)
+                                .scale(Vector2::new(0.08, 0.08))
+                                .offset(Point2::new(0.47, 0.7))
+                                .rotation((self.vel.y).atan2(self.vel.x) + std::f32::consts::FRAC_PI_2);
+        graphics::draw(ctx, &assets.predator, drawparams)
     }
 }
\ No newline at end of file
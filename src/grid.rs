@@ -0,0 +1,129 @@
+use ggez::nalgebra::Point2;
+
+/// Uniform grid bucketing bird indices by position, used to find neighbour
+/// candidates without visiting every pair of birds.
+///
+/// Positions outside the world are clamped into the border cells, so a query
+/// never misses a bird that strayed off-screen before wrapping.
+#[derive(Debug, Clone)]
+pub struct SpatialGrid {
+    cell_size: f32,
+    columns: usize,
+    rows: usize,
+    cells: Vec<Vec<usize>>,
+}
+
+impl SpatialGrid {
+    pub fn new(width: f32, height: f32, cell_size: f32) -> Self {
+        let mut grid = SpatialGrid {
+            cell_size: 1.0,
+            columns: 0,
+            rows: 0,
+            cells: Vec::new(),
+        };
+        grid.resize(width, height, cell_size);
+        grid
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// Changes the grid layout and empties every cell.
+    pub fn resize(&mut self, width: f32, height: f32, cell_size: f32) {
+        let cell_size = cell_size.max(1.0);
+        let columns = ((width / cell_size).ceil() as usize).max(1);
+        let rows = ((height / cell_size).ceil() as usize).max(1);
+
+        if self.cell_size != cell_size || self.columns != columns || self.rows != rows {
+            self.cell_size = cell_size;
+            self.columns = columns;
+            self.rows = rows;
+            self.cells = vec![Vec::new(); columns * rows];
+        }
+        else {
+            self.clear();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        for cell in self.cells.iter_mut() {
+            cell.clear();
+        }
+    }
+
+    /// Empties the grid and inserts `positions`, indexed by their order.
+    pub fn rebuild<I: IntoIterator<Item = Point2<f32>>>(&mut self, positions: I) {
+        self.clear();
+        for (index, pos) in positions.into_iter().enumerate() {
+            self.insert(index, pos);
+        }
+    }
+
+    pub fn insert(&mut self, index: usize, pos: Point2<f32>) {
+        let cell = self.cell_index(pos);
+        self.cells[cell].push(index);
+    }
+
+    /// Moves `index` to the cell of `to` if it left the cell of `from`.
+    pub fn relocate(&mut self, index: usize, from: Point2<f32>, to: Point2<f32>) {
+        let old_cell = self.cell_index(from);
+        let new_cell = self.cell_index(to);
+        if old_cell == new_cell {
+            return;
+        }
+        if let Some(slot) = self.cells[old_cell].iter().position(|&i| i == index) {
+            self.cells[old_cell].swap_remove(slot);
+        }
+        self.cells[new_cell].push(index);
+    }
+
+    /// Fills `result` with the indices of every entry whose cell lies within
+    /// `radius` of `pos`, sorted ascending.
+    ///
+    /// The candidates still have to be filtered by exact distance; sorting keeps
+    /// the summation order the same as a brute-force loop over all birds.
+    pub fn query(&self, pos: Point2<f32>, radius: f32, result: &mut Vec<usize>) {
+        result.clear();
+        self.collect(pos, radius, result);
+        result.sort_unstable();
+    }
+
+    /// Like `query`, but for a `width` by `height` world whose opposite edges
+    /// touch, so entries just across an edge are found too.
+    pub fn query_wrapped(&self, pos: Point2<f32>, radius: f32, width: f32, height: f32, result: &mut Vec<usize>) {
+        result.clear();
+        for &shift_x in [-width, 0.0, width].iter() {
+            for &shift_y in [-height, 0.0, height].iter() {
+                let shifted = Point2::new(pos.x + shift_x, pos.y + shift_y);
+                if shifted.x + radius >= 0.0 && shifted.x - radius <= width && shifted.y + radius >= 0.0 && shifted.y - radius <= height {
+                    self.collect(shifted, radius, result);
+                }
+            }
+        }
+        result.sort_unstable();
+        result.dedup();
+    }
+
+    fn collect(&self, pos: Point2<f32>, radius: f32, result: &mut Vec<usize>) {
+        let (min_column, min_row) = self.cell_coords(Point2::new(pos.x - radius, pos.y - radius));
+        let (max_column, max_row) = self.cell_coords(Point2::new(pos.x + radius, pos.y + radius));
+
+        for row in min_row..=max_row {
+            for column in min_column..=max_column {
+                result.extend_from_slice(&self.cells[row * self.columns + column]);
+            }
+        }
+    }
+
+    fn cell_coords(&self, pos: Point2<f32>) -> (usize, usize) {
+        let column = (pos.x / self.cell_size).floor().max(0.0) as usize;
+        let row = (pos.y / self.cell_size).floor().max(0.0) as usize;
+        (column.min(self.columns - 1), row.min(self.rows - 1))
+    }
+
+    fn cell_index(&self, pos: Point2<f32>) -> usize {
+        let (column, row) = self.cell_coords(pos);
+        row * self.columns + column
+    }
+}
@@ -1,4 +1,17 @@
 pub mod entities;
 pub mod assets;
+pub mod camera;
+pub mod cli;
+pub mod config;
 pub mod debug;
-pub mod tools;
\ No newline at end of file
+pub mod grid;
+pub mod metrics;
+pub mod minimap;
+pub mod panel;
+pub mod rules;
+pub mod scene;
+pub mod simulation;
+pub mod snapshot;
+pub mod species;
+pub mod tools;
+pub mod trajectory;
\ No newline at end of file
@@ -1,4 +1,4 @@
-use ggez::{conf::FullscreenType, nalgebra::distance};
+use ggez::conf::FullscreenType;
 use ggez::conf::{Conf, WindowMode};
 use ggez::event;
 use ggez::filesystem;
@@ -9,17 +9,26 @@ use ggez::nalgebra::{ Point2, Vector2 };
 use ggez::timer;
 use ggez::{Context, ContextBuilder, GameResult};
 use graphics::MeshBuilder;
-use rand::Rng;
-use rand::rngs::ThreadRng;
+use structopt::StructOpt;
 
-use boids::entities::Bird;
-use boids::entities::Obstacle;
 use boids::assets::Assets;
+use boids::camera::Camera;
+use boids::cli::{Options, Recorders};
+use boids::config::{Config, ConfigWatcher};
 use boids::debug;
-use boids::tools::Tools;
+use boids::entities::{Motion, Shape};
+use boids::minimap::Minimap;
+use boids::panel::ParameterPanel;
+use boids::scene::{Scene, SpawnRegion};
+use boids::snapshot::Snapshot;
+use boids::rules::ObstacleEvasion;
+use boids::simulation::{NeighbourSearch, Neighbourhood, ObstacleAvoidance, Simulation, UpdateMode};
 
 use std::env;
+use std::io;
 use std::path;
+use std::process;
+use std::time::Instant;
 
 #[derive(Debug, Default)]
 struct InputState {
@@ -32,86 +41,121 @@ enum Pause{
     ToPause,
     Paused
 }
+#[derive(Debug)]
 enum Entity{
     Bird,
-    Obstacle
+    Obstacle,
+    Wall,
+    Rect,
+    Polygon,
+    Predator
+}
+#[derive(Debug)]
+enum ObstacleMotion{
+    Static,
+    Drift,
+    Orbit,
+    Path,
+    FollowMouse
 }
 struct MainState {
-    rng: ThreadRng,
+    simulation: Simulation,
     assets: Assets,
-    birds: Vec<Bird>,
-    obstacles: Vec<Obstacle>,
-    separation_rule: bool,
-    alignment_rule: bool,
-    cohesion_rule: bool,
-    random_movement_rule: bool,
-    screen_width: f32,
-    screen_height: f32,
+    camera: Camera,
+    /// Screen position of the mouse while dragging the view with the right button.
+    pan_from: Option<Point2<f32>>,
+    minimap: Minimap,
+    /// Whether the left button went down on the minimap and still moves the view.
+    minimap_drag: bool,
     spawn_cooldown: f32,
     pause: Pause,
     debug_circles: bool,
     debug_vectors: bool,
-    spawn_entity: Entity
+    show_metrics: bool,
+    panel: ParameterPanel,
+    spawn_entity: Entity,
+    spawn_species: usize,
+    spawn_motion: ObstacleMotion,
+    drag_start: Option<Point2<f32>>,
+    polygon_vertices: Vec<Point2<f32>>,
+    /// Command line options, whose overrides are applied to every reloaded config.
+    options: Options,
+    /// The config as last loaded, to tell which keys an edit of the file changed.
+    config: Config,
+    config_watcher: ConfigWatcher,
+    config_error: Option<String>,
+    recorders: Recorders,
+    snapshot_path: path::PathBuf,
+    scene_path: path::PathBuf,
+    /// Spawn regions of the loaded scene, kept when the layout is saved.
+    scene_spawn: Vec<SpawnRegion>
 }
 
 impl MainState {
-    pub const ALIGNMENT_VIEW_DISTANCE: f32 = 100_f32;
-    pub const SEPARATION_VIEW_DISTANCE: f32 = 30_f32;
-    pub const COHESION_VIEW_DISTANCE: f32 = 100_f32;
-    pub const OBSTACLE_RADIUS: f32 = 50.0;
-
-    pub const MAX_SPEED: f32 = 3.5_f32;
-    pub const MAX_STEERING_VELOCITY: f32 = 0.16_f32;
-    pub const RANDOM_MOVEMENT: f32 = 0.1_f32;
-
-    pub const ALIGNMENT_MODIFIER: f32 = 1.6;
-    pub const SEPARATION_MODIFIER: f32 = 2.0;
-    pub const COHESION_MODIFIER: f32 = 1.0;
-    pub const OBSTACLE_MODIFIER: f32 = 2.5;
-
-    fn new(ctx: &mut Context, conf: &Conf) -> GameResult<MainState> {
-        let screen_width = conf.window_mode.width;
-        let screen_height = conf.window_mode.height;
-        let assets =  Assets::new(ctx)?;
-        let birds = vec![Bird::new(Point2::new(screen_width / 2.0, screen_height / 2.0), Vector2::new(0.1, 0.1))];
-        let s = MainState {
-            rng: rand::thread_rng(),
+    fn new(ctx: &mut Context, simulation: Simulation, options: Options, config: Config, recorders: Recorders, scene_spawn: Vec<SpawnRegion>) -> GameResult<MainState> {
+        let mut assets =  Assets::new(ctx)?;
+        assets.load_species(ctx, &simulation.species)?;
+        let (screen_width, screen_height) = match graphics::drawable_size(ctx) {
+            (width, height) if width > 0.0 && height > 0.0 => (width, height),
+            _ => (simulation.width, simulation.height)
+        };
+        let panel = ParameterPanel::new(Point2::new(screen_width - ParameterPanel::width(), 0.0));
+        let mut s = MainState {
+            camera: Camera::new(screen_width, screen_height),
+            pan_from: None,
+            minimap: Minimap::new(),
+            minimap_drag: false,
+            simulation,
             assets: assets,
-            birds: birds,
-            obstacles: Vec::new(),
-            separation_rule: true,
-            alignment_rule: true,
-            cohesion_rule: true,
-            random_movement_rule: true,
-            screen_width: conf.window_mode.width,
-            screen_height: conf.window_mode.height,
             spawn_cooldown: 0.05 as f32,
             pause: Pause::Running,
             debug_circles: false,
             debug_vectors: false,
-            spawn_entity: Entity::Bird
+            show_metrics: false,
+            panel,
+            spawn_entity: Entity::Bird,
+            spawn_species: 0,
+            spawn_motion: ObstacleMotion::Static,
+            drag_start: None,
+            polygon_vertices: Vec::new(),
+            config_watcher: ConfigWatcher::new(options.config_path()),
+            config_error: None,
+            recorders,
+            snapshot_path: options.snapshot.clone(),
+            scene_path: options.scene_path(),
+            options,
+            config,
+            scene_spawn
         };
+        // centred on the world, which a scene may have resized
+        s.reset_camera();
 
         Ok(s)
     }
 
     fn toggle_rule(&mut self, rule: &str) {
         match rule {
-            "separation" =>{
-                self.separation_rule = !self.separation_rule;
-                println!("Separation rule is {}", self.separation_rule);
+            "grid" => {
+                self.simulation.neighbour_search = match self.simulation.neighbour_search {
+                    NeighbourSearch::Grid => NeighbourSearch::BruteForce,
+                    NeighbourSearch::BruteForce => NeighbourSearch::Grid
+                };
+                println!("Neighbour search is {:?}", self.simulation.neighbour_search);
             },
-            "alignment" => {
-                self.alignment_rule = !self.alignment_rule;
-                println!("Alignment rule is {}", self.alignment_rule);
+            "update_mode" => {
+                self.simulation.update_mode = match self.simulation.update_mode {
+                    UpdateMode::DoubleBuffered => UpdateMode::Sequential,
+                    UpdateMode::Sequential => UpdateMode::DoubleBuffered
+                };
+                println!("Update mode is {:?}", self.simulation.update_mode);
             },
-            "cohesion" => {
-                self.cohesion_rule = !self.cohesion_rule;
-                println!("Cohesion rule is {}", self.cohesion_rule);
+            "boundary" => {
+                self.simulation.parameters.boundary = self.simulation.parameters.boundary.next();
+                println!("Boundary is {}", self.simulation.parameters.boundary);
             },
-            "random" => {
-                self.random_movement_rule = !self.random_movement_rule;
-                println!("Random movement rule is {}", self.random_movement_rule);
+            "neighbourhood" => {
+                self.simulation.parameters.neighbourhood = self.simulation.parameters.neighbourhood.next();
+                println!("Neighbourhood is {}", self.simulation.parameters.neighbourhood);
             },
             "debug_circles" => {
                 self.debug_circles = !self.debug_circles;
@@ -121,8 +165,126 @@ impl MainState {
                 self.debug_vectors = !self.debug_vectors;
                 println!("Debug vectors is {}", self.debug_vectors);
             },
-            _ => ()
+            "metrics" => {
+                self.show_metrics = !self.show_metrics;
+                println!("Metrics is {}", self.show_metrics);
+            },
+            "minimap" => {
+                self.minimap.visible = !self.minimap.visible;
+                println!("Minimap is {}", self.minimap.visible);
+            },
+            "panel" => {
+                self.panel.visible = !self.panel.visible;
+                println!("Parameter panel is {}", self.panel.visible);
+            },
+            _ => {
+                if let Some(enabled) = self.simulation.toggle_rule(rule) {
+                    println!("{} rule is {}", rule, enabled);
+                }
+            }
+        }
+    }
+    /// Logs the metrics and trajectories of the current tick, giving up on
+    /// recording if a file cannot be written.
+    fn record(&mut self) {
+        if let Err(e) = self.recorders.record(&self.simulation) {
+            println!("ERROR writing output file -> {}", e);
+            self.recorders = Recorders { metrics_log: None, trajectory_recorder: None };
+        }
+    }
+
+    /// Applies weights, view distances and speed limits from the config file
+    /// if it was edited, with the command line overrides. Only the keys that
+    /// changed in the file are applied. Rule toggles, the window and the world
+    /// size are left as they are, and species can be changed but not added or
+    /// removed.
+    fn reload_config(&mut self, ctx: &mut Context) {
+        match self.config_watcher.poll() {
+            Some(Ok(mut config)) => {
+                self.options.apply_overrides(&mut config);
+                if let Err(e) = config.validate() {
+                    println!("ERROR in {} -> {}", self.config_watcher.path().display(), e);
+                    self.config_error = Some(format!("{}: {}", self.config_watcher.path().display(), e));
+                    return;
+                }
+                let species = config.species();
+                if species.len() != self.simulation.species.len() {
+                    println!("ERROR in {} -> species cannot be added or removed while running", self.config_watcher.path().display());
+                    self.config_error = Some(format!("{}: species cannot be added or removed while running", self.config_watcher.path().display()));
+                    return;
+                }
+                if self.assets.load_species(ctx, &species).is_err() {
+                    println!("ERROR in {} -> cannot load species sprites", self.config_watcher.path().display());
+                    self.config_error = Some(format!("{}: cannot load species sprites", self.config_watcher.path().display()));
+                    return;
+                }
+                if let Err(e) = config.reapply(&self.config, &mut self.simulation) {
+                    println!("ERROR in {} -> {}", self.config_watcher.path().display(), e);
+                    self.config_error = Some(format!("{}: {}", self.config_watcher.path().display(), e));
+                    return;
+                }
+                self.config = config;
+                self.config_error = None;
+                println!("Reloaded config from {}", self.config_watcher.path().display());
+            },
+            Some(Err(e)) => {
+                println!("ERROR in {} -> {}", self.config_watcher.path().display(), e);
+                self.config_error = Some(format!("{}: {}", self.config_watcher.path().display(), e));
+            },
+            None => ()
+        }
+    }
+    fn save_snapshot(&mut self) {
+        match self.simulation.snapshot().save(&self.snapshot_path) {
+            Ok(()) => println!("Saved snapshot of tick {} to {}", self.simulation.tick, self.snapshot_path.display()),
+            Err(e) => println!("ERROR in {} -> {}", self.snapshot_path.display(), e)
+        }
+    }
+    /// Writes the world size and obstacles to the scene file, with the spawn
+    /// regions of the scene it was loaded from.
+    fn save_scene(&mut self) {
+        let scene = Scene {
+            spawn: self.scene_spawn.clone(),
+            ..Scene::from_simulation(&self.simulation)
+        };
+        match scene.save(&self.scene_path) {
+            Ok(()) => println!("Saved scene with {} obstacles to {}", scene.obstacles.len(), self.scene_path.display()),
+            Err(e) => println!("ERROR in {} -> {}", self.scene_path.display(), e)
+        }
+    }
+    /// Replaces the simulation with the one in the snapshot file. Rule toggles,
+    /// parameters and species come from the snapshot rather than the config.
+    /// The `--metrics` and `--trajectory` files are started over, as their
+    /// headers name the rules of the replaced simulation.
+    fn load_snapshot(&mut self, ctx: &mut Context) {
+        let simulation = match Snapshot::load(&self.snapshot_path).and_then(Simulation::from_snapshot) {
+            Ok(simulation) => simulation,
+            Err(e) => {
+                println!("ERROR in {} -> {}", self.snapshot_path.display(), e);
+                return;
+            }
+        };
+        if self.assets.load_species(ctx, &simulation.species).is_err() {
+            println!("ERROR in {} -> cannot load species sprites", self.snapshot_path.display());
+            return;
         }
+        self.simulation = simulation;
+        self.spawn_species = 0;
+        self.drag_start = None;
+        self.polygon_vertices.clear();
+        // the snapshot may be of a world of another size
+        self.minimap_drag = false;
+        self.reset_camera();
+        if let Err(e) = self.recorders.restart(&self.options) {
+            println!("ERROR writing output file -> {}", e);
+        }
+        println!("Loaded snapshot of tick {} from {}", self.simulation.tick, self.snapshot_path.display());
+    }
+    /// Shows the middle of the world at zoom 1 again. Called whenever the
+    /// world is replaced, so the view never starts outside of it.
+    fn reset_camera(&mut self) {
+        let (screen_width, screen_height) = self.camera.screen_size();
+        self.camera = Camera::looking_at(screen_width, screen_height, Point2::new(self.simulation.width / 2.0, self.simulation.height / 2.0));
     }
     fn toggle_pause(&mut self) {
         match self.pause {
@@ -134,8 +296,63 @@ impl MainState {
     fn toggle_spawn(&mut self) {
         match self.spawn_entity {
             Entity::Bird => self.spawn_entity = Entity::Obstacle,
-            Entity::Obstacle => self.spawn_entity = Entity::Bird
+            Entity::Obstacle => self.spawn_entity = Entity::Wall,
+            Entity::Wall => self.spawn_entity = Entity::Rect,
+            Entity::Rect => self.spawn_entity = Entity::Polygon,
+            Entity::Polygon => self.spawn_entity = Entity::Predator,
+            Entity::Predator => self.spawn_entity = Entity::Bird
         }
+        self.drag_start = None;
+        self.polygon_vertices.clear();
+        println!("Spawning {:?}", self.spawn_entity);
+    }
+    /// Turns the clicked vertices into a polygon obstacle.
+    fn close_polygon(&mut self) {
+        if self.polygon_vertices.len() >= 3 {
+            let (pos, shape) = Shape::polygon(&self.polygon_vertices);
+            self.simulation.spawn_shaped_obstacle(pos, shape);
+        }
+        self.polygon_vertices.clear();
+    }
+    fn toggle_motion(&mut self) {
+        match self.spawn_motion {
+            ObstacleMotion::Static => self.spawn_motion = ObstacleMotion::Drift,
+            ObstacleMotion::Drift => self.spawn_motion = ObstacleMotion::Orbit,
+            ObstacleMotion::Orbit => self.spawn_motion = ObstacleMotion::Path,
+            ObstacleMotion::Path => self.spawn_motion = ObstacleMotion::FollowMouse,
+            ObstacleMotion::FollowMouse => self.spawn_motion = ObstacleMotion::Static
+        }
+        println!("Obstacle motion is {:?}", self.spawn_motion);
+    }
+    /// Spawns an obstacle at `pos` moving the way selected with `toggle_motion`.
+    fn spawn_obstacle(&mut self, pos: Point2<f32>) {
+        const SPEED: f32 = 1.5;
+        const SIZE: f32 = 150.0;
+
+        match self.spawn_motion {
+            ObstacleMotion::Static => self.simulation.spawn_obstacle(pos),
+            ObstacleMotion::Drift => self.simulation.spawn_drifting_obstacle(pos, SPEED),
+            ObstacleMotion::Orbit => {
+                // orbit around the clicked point
+                let motion = Motion::Orbit { center: pos, radius: SIZE / 2.0, angular_speed: SPEED / (SIZE / 2.0), angle: 0.0 };
+                self.simulation.spawn_moving_obstacle(Point2::new(pos.x + SIZE / 2.0, pos.y), motion);
+            },
+            ObstacleMotion::Path => {
+                // a square with the clicked point as its top left corner
+                let waypoints = vec![
+                    Point2::new(pos.x + SIZE, pos.y),
+                    Point2::new(pos.x + SIZE, pos.y + SIZE),
+                    Point2::new(pos.x, pos.y + SIZE),
+                    pos
+                ];
+                self.simulation.spawn_moving_obstacle(pos, Motion::Path { waypoints, speed: SPEED, next: 0 });
+            },
+            ObstacleMotion::FollowMouse => self.simulation.spawn_moving_obstacle(pos, Motion::FollowMouse { speed: SPEED })
+        }
+    }
+    fn toggle_species(&mut self) {
+        self.spawn_species = (self.spawn_species + 1) % self.simulation.species.len();
+        println!("Spawning {} birds", self.simulation.species[self.spawn_species].name);
     }
 }
 
@@ -145,162 +362,41 @@ impl event::EventHandler for MainState {
 
         const DESIRED_FPS: u32 = 60;
 
+        self.reload_config(ctx);
+
         while timer::check_update_time(ctx, DESIRED_FPS) {
             let seconds = 1.0 / (DESIRED_FPS as f32);
 
             if self.pause == Pause::Running {
                 self.spawn_cooldown -= seconds;
 
-                if mouse::button_pressed(ctx, mouse::MouseButton::Left) && self.spawn_cooldown <= 0.0 {
-                    let mouse_position = mouse::position(ctx);
-
-                    let x = mouse_position.x;
-                    let y = mouse_position.y;
+                let mouse_position = mouse::position(ctx);
+                let mouse_position = Point2::new(mouse_position.x, mouse_position.y);
+                let mouse_world = self.camera.screen_to_world(mouse_position);
+                self.simulation.mouse = Some(mouse_world);
 
+                let over_ui = self.panel.is_dragging() || self.panel.contains(mouse_position)
+                    || self.minimap_drag || self.minimap.contains(&self.camera, &self.simulation, mouse_position);
+                if mouse::button_pressed(ctx, mouse::MouseButton::Left) && self.spawn_cooldown <= 0.0 && !over_ui {
                     match self.spawn_entity {
                         Entity::Bird => {
-                            let new_bird = Bird::new(Point2::new(x*0.99, y*0.96), Vector2::new(self.rng.gen_range(-0.1 .. 0.1), self.rng.gen_range(-0.1 .. 0.1)) );
-                            self.birds.push(new_bird);
+                            self.simulation.spawn_bird_of_species(mouse_world, self.spawn_species);
                             self.spawn_cooldown = 0.05;
                         },
                         Entity::Obstacle => {
-                            let new_obstacle = Obstacle::new(Point2::new(x*0.99, y*0.96), MainState::OBSTACLE_RADIUS);
-                            self.obstacles.push(new_obstacle);
-                            self.spawn_cooldown = 0.05;
-                        }
-                    }
-                }
-                for i in 0..self.birds.len() {
-
-                    // ------------------------------------------ALIGNMENT RULE:--------------------------------------------
-                    let mut velocity_sum_of_neigbours: Vector2<f32> = Vector2::new(0.0, 0.0);
-                    let mut number_of_neighbours = 0;
-
-                    if self.alignment_rule {
-                        for j in 0..self.birds.len() {
-                            let distance: f32 = distance(&self.birds[i].pos, &self.birds[j].pos);
-                            if distance > 0.0 && distance <= MainState::ALIGNMENT_VIEW_DISTANCE {
-                                velocity_sum_of_neigbours += self.birds[j].vel;
-                                number_of_neighbours += 1;
-                            }
-                        }
-
-                        if number_of_neighbours > 0 {
-                            velocity_sum_of_neigbours /= number_of_neighbours as f32;
-                            Tools::normalize_vector(&mut velocity_sum_of_neigbours);
-                            velocity_sum_of_neigbours *= MainState::MAX_SPEED;
-                            velocity_sum_of_neigbours -= self.birds[i].vel;
-                            Tools::limit_vector(&mut velocity_sum_of_neigbours, MainState::MAX_STEERING_VELOCITY);
-                        }
-                        else {
-                            velocity_sum_of_neigbours = Vector2::new(0.0, 0.0);
-
-                        }
-                        velocity_sum_of_neigbours *= MainState::ALIGNMENT_MODIFIER;
-                    }
-
-                    // ----------------------------------------SEPARATION RULE:-----------------------------------------------
-                    let mut steer_away_velocity: Vector2<f32> = Vector2::new(0.0, 0.0);
-                    let mut number_of_neighbours = 0;
-
-                    if self.separation_rule {
-                        for j in 0..self.birds.len() {
-                            let distance: f32 = distance(&self.birds[i].pos, &self.birds[j].pos);
-
-                            if distance > 0.0 && distance <= MainState::SEPARATION_VIEW_DISTANCE {
-                                let mut vector_away_from_neightbour: Vector2<f32> = self.birds[i].pos - self.birds[j].pos;
-                                Tools::normalize_vector(&mut vector_away_from_neightbour);
-                                vector_away_from_neightbour /= distance;
-                                steer_away_velocity += vector_away_from_neightbour;
-                                number_of_neighbours += 1;
-                            }
-                        }
-
-                        if number_of_neighbours > 0 {
-                            steer_away_velocity /= number_of_neighbours as f32;
-                        }
-                        if Tools::vector_length(&steer_away_velocity) > 0.0 {
-                            Tools::normalize_vector(&mut steer_away_velocity);
-                            steer_away_velocity *= MainState::MAX_SPEED;
-                            steer_away_velocity -= self.birds[i].vel;
-                            Tools::limit_vector(&mut steer_away_velocity, MainState::MAX_STEERING_VELOCITY);
-                        }
-                        steer_away_velocity *= MainState::SEPARATION_MODIFIER;
-                    }
-
-                    // ------------------------------------------COHESION RULE:----------------------------------------------
-                    let mut average_position: Point2<f32> = Point2::new(0.0, 0.0);
-                    let mut number_of_neighbours = 0;
-                    let mut steer_towards_velocity: Vector2<f32> = Vector2::new(0.0, 0.0);
-
-                    if self.cohesion_rule {
-                        for j in 0..self.birds.len() {
-                            let distance: f32 = distance(&self.birds[i].pos, &self.birds[j].pos);
-                            if distance > 0.0 && distance <= MainState::COHESION_VIEW_DISTANCE {
-                                average_position.x += self.birds[j].pos.x;
-                                average_position.y += self.birds[j].pos.y;
-                                number_of_neighbours += 1;
-                            }
-                        }
-
-                        if number_of_neighbours > 0 {
-                            average_position /= number_of_neighbours as f32;
-                            let mut vector_towards_average: Vector2<f32> = Tools::get_vec_from_to(average_position, self.birds[i].pos);
-                            Tools::normalize_vector(&mut vector_towards_average);
-                            vector_towards_average *= MainState::MAX_SPEED;
-
-                            steer_towards_velocity = vector_towards_average - self.birds[i].vel;
-                            Tools::limit_vector(&mut steer_towards_velocity, MainState::MAX_STEERING_VELOCITY);
-                        }
-                        steer_towards_velocity *= MainState::COHESION_MODIFIER;
-                    }
-
-                    // ------------------------------------------RANDOM MOVEMENT:----------------------------------------------
-                    let mut random_movement: Vector2<f32> = Vector2::new(0.0, 0.0);
-                    if self.random_movement_rule {
-                        random_movement = Vector2::new(
-                            self.rng.gen_range(-MainState::RANDOM_MOVEMENT .. MainState::RANDOM_MOVEMENT), 
-                            self.rng.gen_range(-MainState::RANDOM_MOVEMENT .. MainState::RANDOM_MOVEMENT)
-                        );
-                    }
-
-                    // ------------------------------------------OBSTACLE EVASION:----------------------------------------------
-                    let mut obstacle_evasion: Vector2<f32> = Vector2::new(0.0, 0.0);
-                    for obstacle in self.obstacles.iter() {
-                        let distance: f32 = distance(&self.birds[i].pos, &obstacle.pos);
-                        if distance <= MainState::OBSTACLE_RADIUS {
-                            let mut vector_away_from_obstacle: Vector2<f32> = self.birds[i].pos - obstacle.pos;
-                            Tools::normalize_vector(&mut vector_away_from_obstacle);
-                            vector_away_from_obstacle /= distance;
-                            obstacle_evasion += vector_away_from_obstacle;
-                            number_of_neighbours += 1;
-                        }
-                    }
-                    if number_of_neighbours > 0 {
-                        obstacle_evasion /= number_of_neighbours as f32;
-                    }
-                    if Tools::vector_length(&obstacle_evasion) > 0.0 {
-                        Tools::normalize_vector(&mut obstacle_evasion);
-                        obstacle_evasion *= MainState::MAX_SPEED;
-                        obstacle_evasion -= self.birds[i].vel;
-                        Tools::limit_vector(&mut obstacle_evasion, MainState::MAX_STEERING_VELOCITY);
+                            self.spawn_obstacle(mouse_world);
+                            self.spawn_cooldown = if let ObstacleMotion::Static = self.spawn_motion { 0.05 } else { 0.5 };
+                        },
+                        Entity::Predator => {
+                            self.simulation.spawn_predator(mouse_world);
+                            self.spawn_cooldown = 0.5;
+                        },
+                        // drawn with mouse_button_down_event and mouse_button_up_event
+                        Entity::Wall | Entity::Rect | Entity::Polygon => ()
                     }
-                    obstacle_evasion *= MainState::OBSTACLE_MODIFIER;
-                    // ---------------------------------------------------------------------------------------------------------
-                    
-                    self.birds[i].update(
-                        velocity_sum_of_neigbours,
-                        steer_away_velocity,  
-                        steer_towards_velocity,
-                        random_movement,
-                        obstacle_evasion,
-                        MainState::MAX_SPEED,
-                        self.screen_width, self.screen_height);
                 }
-                // remove entities that are not alive
-                self.birds.retain(|bird| bird.is_alive);
-                self.obstacles.retain(|obstacle| obstacle.is_alive);
-                    
+                self.simulation.step(seconds);
+                self.record();
             }   
         }
         Ok(())
@@ -311,127 +407,301 @@ impl event::EventHandler for MainState {
             event::KeyCode::S => self.toggle_rule("separation"),
             event::KeyCode::A => self.toggle_rule("alignment"),
             event::KeyCode::C => self.toggle_rule("cohesion"),
-            event::KeyCode::R => self.toggle_rule("random"),
+            event::KeyCode::R => self.toggle_rule("random_movement"),
+            event::KeyCode::E => self.toggle_rule("obstacle_evasion"),
+            event::KeyCode::G => self.toggle_rule("grid"),
+            event::KeyCode::U => self.toggle_rule("update_mode"),
+            event::KeyCode::W => self.toggle_rule("boundary"),
+            event::KeyCode::K => self.toggle_rule("containment"),
+            event::KeyCode::T => self.toggle_rule("neighbourhood"),
             event::KeyCode::D => self.toggle_rule("debug_circles"),
             event::KeyCode::V => self.toggle_rule("debug_vectors"),
+            event::KeyCode::I => self.toggle_rule("metrics"),
+            event::KeyCode::Tab => self.toggle_rule("panel"),
+            event::KeyCode::Z => self.reset_camera(),
+            event::KeyCode::H => self.toggle_rule("minimap"),
+            event::KeyCode::F5 => self.save_snapshot(),
+            event::KeyCode::F6 => self.save_scene(),
+            event::KeyCode::F9 => self.load_snapshot(ctx),
             event::KeyCode::P => self.toggle_pause(),
             event::KeyCode::Space => self.toggle_spawn(),
+            event::KeyCode::N => self.toggle_species(),
+            event::KeyCode::M => self.toggle_motion(),
+            event::KeyCode::Return => self.close_polygon(),
             event::KeyCode::Escape => event::quit(ctx),
-            event::KeyCode::B => {
-                for bird in self.birds.iter_mut() {
-                    bird.is_alive = false;
-                }
-            },
-            event::KeyCode::O => {
-                for obstacle in self.obstacles.iter_mut() {
-                    obstacle.is_alive = false;
-                }
-            },
+            event::KeyCode::B => self.simulation.kill_birds(),
+            event::KeyCode::O => self.simulation.kill_obstacles(),
+            event::KeyCode::X => self.simulation.kill_predators(),
+            event::KeyCode::F => self.toggle_rule("flee"),
             _ => (), // Do nothing
         }
     }
 
+    fn mouse_button_down_event(&mut self, _ctx: &mut Context, button: event::MouseButton, x: f32, y: f32) {
+        if button == event::MouseButton::Right {
+            self.pan_from = Some(Point2::new(x, y));
+            return;
+        }
+        if button != event::MouseButton::Left {
+            return;
+        }
+        if self.panel.mouse_down(Point2::new(x, y), &mut self.simulation) {
+            return;
+        }
+        if self.minimap.contains(&self.camera, &self.simulation, Point2::new(x, y)) {
+            self.camera.center = self.minimap.minimap_to_world(&self.camera, &self.simulation, Point2::new(x, y));
+            self.minimap_drag = true;
+            return;
+        }
+        let pos = self.camera.screen_to_world(Point2::new(x, y));
+        match self.spawn_entity {
+            Entity::Wall | Entity::Rect => self.drag_start = Some(pos),
+            Entity::Polygon => self.polygon_vertices.push(pos),
+            _ => ()
+        }
+    }
+
+    fn mouse_button_up_event(&mut self, _ctx: &mut Context, button: event::MouseButton, x: f32, y: f32) {
+        if button == event::MouseButton::Right {
+            self.pan_from = None;
+            return;
+        }
+        if button != event::MouseButton::Left || self.panel.mouse_up() {
+            return;
+        }
+        if self.minimap_drag {
+            self.minimap_drag = false;
+            return;
+        }
+        if let Some(start) = self.drag_start.take() {
+            // shorter walls and thinner rectangles are most likely stray clicks
+            const MIN_SIZE: f32 = 5.0;
+
+            let end = self.camera.screen_to_world(Point2::new(x, y));
+            let size = end - start;
+            let too_small = match self.spawn_entity {
+                Entity::Rect => size.x.abs() < MIN_SIZE || size.y.abs() < MIN_SIZE,
+                _ => size.norm() < MIN_SIZE
+            };
+            if too_small {
+                return;
+            }
+            let (pos, shape) = match self.spawn_entity {
+                Entity::Rect => Shape::rect(start, end),
+                _ => Shape::wall(start, end)
+            };
+            self.simulation.spawn_shaped_obstacle(pos, shape);
+        }
+    }
+
+    fn mouse_motion_event(&mut self, _ctx: &mut Context, x: f32, y: f32, _dx: f32, _dy: f32) {
+        let mouse_position = Point2::new(x, y);
+        if let Some(pan_from) = self.pan_from {
+            self.camera.pan(mouse_position - pan_from);
+            self.pan_from = Some(mouse_position);
+        }
+        if self.minimap_drag {
+            self.camera.center = self.minimap.minimap_to_world(&self.camera, &self.simulation, mouse_position);
+        }
+        self.panel.mouse_motion(mouse_position, &mut self.simulation);
+    }
+
+    /// Scrolling over the parameter panel changes the slider under the mouse,
+    /// and anywhere else zooms around the mouse.
+    fn mouse_wheel_event(&mut self, ctx: &mut Context, _x: f32, y: f32) {
+        if y == 0.0 {
+            return;
+        }
+        let mouse_position = mouse::position(ctx);
+        let mouse_position = Point2::new(mouse_position.x, mouse_position.y);
+        if !self.panel.scroll(mouse_position, y.signum(), &mut self.simulation) {
+            self.camera.zoom_at(mouse_position, 1.1_f32.powf(y));
+        }
+    }
+
+    fn resize_event(&mut self, ctx: &mut Context, width: f32, height: f32) {
+        self.camera.resize(width, height);
+        self.panel.pos = Point2::new(width - ParameterPanel::width(), 0.0);
+        graphics::set_screen_coordinates(ctx, self.camera.screen_rect()).unwrap();
+    }
+
     fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
         
         let background = graphics::Color::from_rgb(30, 35, 56);
 
+        let (screen_width, screen_height) = self.camera.screen_size();
+
         if self.pause == Pause::Running {
             graphics::clear(ctx, background);
-            
+
+            // draw the world through the camera
+            graphics::set_screen_coordinates(ctx, self.camera.world_rect())?;
+            let world_outline = MeshBuilder::new().rectangle(
+                graphics::DrawMode::stroke(1.0 / self.camera.zoom),
+                graphics::Rect::new(0.0, 0.0, self.simulation.width, self.simulation.height),
+                (70, 75, 100).into()).build(ctx)?;
+            graphics::draw(ctx, &world_outline, graphics::DrawParam::default())?;
+
             // draw entities
-            for bird in self.birds.iter_mut() {
-                bird.draw(ctx, &self.assets)?;
+            for bird in self.simulation.birds.iter_mut() {
+                bird.draw(ctx, &self.assets, &self.simulation.species[bird.species])?;
             }
 
-            for obstacle in self.obstacles.iter_mut() {
+            for obstacle in self.simulation.obstacles.iter_mut() {
                 obstacle.draw(ctx, &self.assets)?;
             }
 
+            for predator in self.simulation.predators.iter_mut() {
+                predator.draw(ctx, &self.assets)?;
+            }
+
+            // draw the obstacle being placed
+            let mouse_position = mouse::position(ctx);
+            let mouse_position = self.camera.screen_to_world(Point2::new(mouse_position.x, mouse_position.y));
+            if let Some(start) = self.drag_start {
+                let (pos, shape) = match self.spawn_entity {
+                    Entity::Rect => Shape::rect(start, mouse_position),
+                    _ => Shape::wall(start, mouse_position)
+                };
+                let mut outline = shape.vertices(pos);
+                outline.push(outline[0]);
+                // a degenerate outline has no mesh, so there is nothing to preview yet
+                if let Ok(preview) = graphics::Mesh::new_line(ctx, &outline, 1.0, (150, 150, 170).into()) {
+                    graphics::draw(ctx, &preview, graphics::DrawParam::default())?;
+                }
+            }
+            if !self.polygon_vertices.is_empty() {
+                let mut outline = self.polygon_vertices.clone();
+                outline.push(mouse_position);
+                if let Ok(preview) = graphics::Mesh::new_line(ctx, &outline, 1.0, (150, 150, 170).into()) {
+                    graphics::draw(ctx, &preview, graphics::DrawParam::default())?;
+                }
+            }
+
             if self.debug_circles || self.debug_vectors || debug::is_active() {
-                for bird in &mut self.birds {
+                for bird in &mut self.simulation.birds {
                     if self.debug_circles{
+                        let parameters = self.simulation.species[bird.species].parameters_or(&self.simulation.parameters);
                         debug::draw_debug_circles(
-                            bird.alignment_view_distance_circle(ctx, MainState::ALIGNMENT_VIEW_DISTANCE),
-                            bird.separation_view_distance_circle(ctx, MainState::SEPARATION_VIEW_DISTANCE),
+                            bird.alignment_view_distance_circle(ctx, parameters.alignment_view_distance, parameters.alignment_field_of_view),
+                            bird.separation_view_distance_circle(ctx, parameters.separation_view_distance, parameters.separation_field_of_view),
                             bird.center_point(ctx),
                             ctx).
                         unwrap();
+                        if parameters.obstacle_avoidance == ObstacleAvoidance::LookAhead {
+                            let feelers = ObstacleEvasion::feelers(bird, parameters).into_iter()
+                                .map(|(dir, length)| bird.feeler(ctx, dir, length))
+                                .collect();
+                            debug::draw_debug_vectors(feelers, ctx).
+                            unwrap();
+                        }
                     }
                     if self.debug_vectors {
-                        debug::draw_debug_vectors(
-                            bird.alignment_vector(ctx),
-                            bird.separation_vector(ctx),
-                            bird.cohesion_vector(ctx),
-                            bird.obstacle_vector(ctx),
-                            ctx).
+                        let steering_vectors = self.simulation.rules.iter()
+                            .zip(bird.steering.iter())
+                            .map(|(active, &steering)| bird.steering_vector(ctx, steering, active.rule.color()))
+                            .collect();
+                        debug::draw_debug_vectors(steering_vectors, ctx).
                         unwrap();
                     }
                     // println!("{:?}", bird);
                 }
             }
 
-            // draw UI
-            // draw alignment rule text
+            // draw UI in screen space
+            graphics::set_screen_coordinates(ctx, self.camera.screen_rect())?;
+            // draw rule texts, green when enabled and red when disabled
             let drawparams = graphics::DrawParam::new().scale(Vector2::new(1.0, 1.0)).offset(Point2::new(0.0, 0.0));
+            let mut text_y = screen_height / 2.0 - 40.0;
 
-            let new_drawarams = if self.alignment_rule {
-                drawparams.color((0, 255, 0).into())
-            }
-            else {
-                drawparams.color((255, 0, 0).into())
-            }.dest(Point2::new(0.0, self.screen_height / 2.0 - 40.0));
+            for active in self.simulation.rules.iter() {
+                let new_drawarams = if active.enabled {
+                    drawparams.color((0, 255, 0).into())
+                }
+                else {
+                    drawparams.color((255, 0, 0).into())
+                }.dest(Point2::new(0.0, text_y));
 
-            graphics::draw(ctx, &graphics::Text::new("alignment"), new_drawarams)?;
+                graphics::draw(ctx, &graphics::Text::new(active.rule.name()), new_drawarams)?;
+                text_y += 20.0;
+            }
+            text_y += 10.0;
 
-            // draw separation rule text
-            let new_drawarams = if self.separation_rule {
+            // draw birds count text
+            let new_drawarams = if !self.simulation.birds.is_empty() {
                 drawparams.color((0, 255, 0).into())
             }
             else {
                 drawparams.color((255, 0, 0).into())
-            }.dest(Point2::new(0.0, self.screen_height / 2.0 - 20.0));
-
-            graphics::draw(ctx, &graphics::Text::new("separation"), new_drawarams)?;
+            }.dest(Point2::new(0.0, text_y));
+            graphics::draw(ctx, &graphics::Text::new(format!("Birds:{}", self.simulation.birds.len())), new_drawarams)?;
+            text_y += 20.0;
+
+            // draw the species of spawned birds in its colour
+            if self.simulation.species.len() > 1 {
+                let species = &self.simulation.species[self.spawn_species];
+                let new_drawarams = drawparams.color(species.color.into()).dest(Point2::new(0.0, text_y));
+                graphics::draw(ctx, &graphics::Text::new(format!("Species:{}", species.name)), new_drawarams)?;
+                text_y += 20.0;
+            }
 
-            // draw cohesion rule text
-            let new_drawarams = if self.cohesion_rule {
+            // draw obstacles count text
+            let new_drawarams = if !self.simulation.obstacles.is_empty() {
                 drawparams.color((0, 255, 0).into())
             }
             else {
                 drawparams.color((255, 0, 0).into())
-            }.dest(Point2::new(0.0, self.screen_height / 2.0));
+            }.dest(Point2::new(0.0, text_y));
+            text_y += 20.0;
 
-            graphics::draw(ctx, &graphics::Text::new("cohesion"), new_drawarams)?;
+            graphics::draw(ctx, &graphics::Text::new(format!("Obstacles:{}", self.simulation.obstacles.len())), new_drawarams)?;
 
-            // draw random movement text
-            let new_drawarams = if self.random_movement_rule {
+            // draw predators count text
+            let new_drawarams = if !self.simulation.predators.is_empty() {
                 drawparams.color((0, 255, 0).into())
             }
             else {
                 drawparams.color((255, 0, 0).into())
-            }.dest(Point2::new(0.0, self.screen_height / 2.0 + 20.0));
+            }.dest(Point2::new(0.0, text_y));
+            text_y += 20.0;
 
-            graphics::draw(ctx, &graphics::Text::new("random movement"), new_drawarams)?;
-            
-            // draw birds count text
-            let new_drawarams = if self.birds.len() > 0 {
-                drawparams.color((0, 255, 0).into())
+            graphics::draw(ctx, &graphics::Text::new(format!("Predators:{}", self.simulation.predators.len())), new_drawarams)?;
+
+            // draw boundary mode text
+            let new_drawarams = drawparams.color((255, 255, 255).into()).dest(Point2::new(0.0, text_y));
+            text_y += 20.0;
+
+            graphics::draw(ctx, &graphics::Text::new(format!("Boundary:{}", self.simulation.parameters.boundary)), new_drawarams)?;
+
+            // draw neighbourhood mode text
+            let parameters = &self.simulation.parameters;
+            let neighbourhood = match parameters.neighbourhood {
+                Neighbourhood::Metric => parameters.neighbourhood.to_string(),
+                Neighbourhood::Topological => format!("{} (k={})", parameters.neighbourhood, parameters.topological_neighbours),
+            };
+            let new_drawarams = drawparams.color((255, 255, 255).into()).dest(Point2::new(0.0, text_y));
+            text_y += 30.0;
+
+            graphics::draw(ctx, &graphics::Text::new(format!("Neighbourhood:{}", neighbourhood)), new_drawarams)?;
+
+            // draw flock metrics text
+            if self.show_metrics {
+                let metrics = self.simulation.metrics().to_string();
+                let new_drawarams = drawparams.color((255, 255, 255).into()).dest(Point2::new(0.0, text_y));
+                text_y += 20.0 * metrics.lines().count() as f32 + 10.0;
+
+                graphics::draw(ctx, &graphics::Text::new(metrics), new_drawarams)?;
             }
-            else {
-                drawparams.color((255, 0, 0).into())
-            }.dest(Point2::new(0.0, self.screen_height / 2.0 + 50.0));
-            graphics::draw(ctx, &graphics::Text::new(format!("Birds:{}", self.birds.len())), new_drawarams)?;
 
-            // draw obstacles count text
-            let new_drawarams = if self.obstacles.len() > 0 {
-                drawparams.color((0, 255, 0).into())
+            // draw config reload error text
+            if let Some(config_error) = &self.config_error {
+                let new_drawarams = drawparams.color((255, 0, 0).into()).dest(Point2::new(0.0, text_y));
+                graphics::draw(ctx, &graphics::Text::new(config_error.as_str()), new_drawarams)?;
             }
-            else {
-                drawparams.color((255, 0, 0).into())
-            }.dest(Point2::new(0.0, self.screen_height / 2.0 + 70.0));
 
-            graphics::draw(ctx, &graphics::Text::new(format!("Obstacles:{}", self.obstacles.len())), new_drawarams)?;
-            
+            self.panel.draw(ctx, &self.simulation.parameters)?;
+            self.minimap.draw(ctx, &self.simulation, &self.camera)?;
+
 
             graphics::present(ctx)?;
         }
@@ -439,26 +709,45 @@ impl event::EventHandler for MainState {
             self.pause = Pause::Paused;
             let pause_screen = MeshBuilder::new().rectangle(
                 graphics::DrawMode::fill(), 
-                graphics::Rect::new(0.0, 0.0, self.screen_width, self.screen_height), 
+                graphics::Rect::new(0.0, 0.0, screen_width, screen_height), 
                 (0, 0, 0, 60).into()).build(ctx).unwrap();
 
             graphics::draw(ctx, &pause_screen, graphics::DrawParam::default())?;
             
             // draw menu on pause screen
             let drawparams = graphics::DrawParam::new()
-                                    .dest(Point2::new(self.screen_width / 2.0 - 200.0, self.screen_height / 2.0 - 90.0))
+                                    .dest(Point2::new(screen_width / 2.0 - 200.0, screen_height / 2.0 - 90.0))
                                     .scale(Vector2::new(1.2, 1.2));
             let pause_menu_legend = r"Press:
     ESC to exit
-    SPACE to toggle entity spawning (bird / obstacle)
+    SPACE to toggle entity spawning (bird / obstacle / wall / rect / polygon / predator)
+        drag to draw a wall or rect, click the corners of a polygon and press ENTER
+    N to cycle the species of spawned birds
+    M to cycle obstacle motion (static / drift / orbit / path / follow mouse)
     O to remove obstacles
+    X to remove predators
     B to remove birds
     P to pause and unpause
-    D to show view distances
+    F5 to save a snapshot and F9 to load it
+    F6 to save the obstacles and world size as a scene
+    D to show view cones and feelers
     V to show vectors
+    I to show flock metrics
+    mouse wheel to zoom, drag with the right button to pan and Z to reset the view
+        click or drag on the minimap to move the view there
+    H to hide or show the minimap
+    TAB to show the parameter panel, then drag or scroll over a slider to change it
+    G to toggle grid / brute-force neighbour search
+    U to toggle double-buffered / sequential updates
+    W to cycle the boundary (wrap / bounce / contain / kill)
+    T to toggle metric / topological neighbourhood
     A to toggle Alignment rule
     S to toggle Separation rule
-    C to toggle Cohesion rule";
+    C to toggle Cohesion rule
+    R to toggle Random movement rule
+    E to toggle obstacle Evasion rule
+    F to toggle Flee rule
+    K to toggle containment rule";
             graphics::draw(ctx, &graphics::Text::new(pause_menu_legend), drawparams)?;
 
             graphics::present(ctx)?;
@@ -468,16 +757,91 @@ impl event::EventHandler for MainState {
     }
 }
 
+/// Runs the simulation without a window for `--steps` ticks and prints a summary,
+/// logging to the `--metrics` and `--trajectory` files along the way.
+fn run_headless(mut simulation: Simulation, steps: u64, mut recorders: Recorders) -> io::Result<()> {
+    const SECONDS_PER_TICK: f32 = 1.0 / 60.0;
+
+    let start = Instant::now();
+    recorders.record(&simulation)?;
+    for _ in 0..steps {
+        simulation.step(SECONDS_PER_TICK);
+        recorders.record(&simulation)?;
+    }
+    if let Some(trajectory_recorder) = recorders.trajectory_recorder {
+        trajectory_recorder.finish()?;
+    }
+    let elapsed = start.elapsed();
+
+    println!("Simulated {} ticks in {:.3}s", simulation.tick, elapsed.as_secs_f32());
+    println!("Birds:{}", simulation.birds.len());
+    if simulation.species.len() > 1 {
+        for (i, species) in simulation.species.iter().enumerate() {
+            println!("  {}:{}", species.name, simulation.birds.iter().filter(|bird| bird.species == i).count());
+        }
+    }
+    println!("Obstacles:{}", simulation.obstacles.len());
+    println!("Predators:{}", simulation.predators.len());
+    println!("{}", simulation.metrics());
+    Ok(())
+}
+
 pub fn main() {
+    let options = Options::from_args();
+    let config = match options.load_config() {
+        Ok(config) => config,
+        Err(e) => {
+            println!("ERROR in {} -> {}", options.config_path().display(), e);
+            process::exit(1);
+        }
+    };
+    let scene = match options.load_scene() {
+        Ok(scene) => scene.unwrap_or_default(),
+        Err(e) => {
+            println!("ERROR in {} -> {}", options.scene_path().display(), e);
+            process::exit(1);
+        }
+    };
+    let mut simulation = match options.initial_simulation(&config, scene.world) {
+        Ok(simulation) => simulation,
+        Err(e) => {
+            println!("ERROR in {} -> {}", options.resume.as_ref().unwrap().display(), e);
+            process::exit(1);
+        }
+    };
+    if options.resume.is_none() {
+        if let Err(e) = scene.apply(&mut simulation) {
+            println!("ERROR in {} -> {}", options.scene_path().display(), e);
+            process::exit(1);
+        }
+    }
+    println!("Seed is {}", simulation.seed);
+
+    let recorders = match Recorders::create(&options) {
+        Ok(recorders) => recorders,
+        Err(e) => {
+            println!("ERROR creating output file -> {}", e);
+            process::exit(1);
+        }
+    };
+
+    if options.headless {
+        if let Err(e) = run_headless(simulation, options.steps, recorders) {
+            println!("ERROR writing output file -> {}", e);
+            process::exit(1);
+        }
+        return;
+    }
+
     let conf = Conf::new().
         window_mode(WindowMode {
-            width: 1920.0,
-            height: 1080.0,
-            maximized: true,
-            fullscreen_type: FullscreenType::Desktop,
+            width: config.window.width,
+            height: config.window.height,
+            maximized: config.window.fullscreen,
+            fullscreen_type: if config.window.fullscreen { FullscreenType::Desktop } else { FullscreenType::Windowed },
             ..Default::default()
         });
-    let (mut ctx, mut event_loop) = ContextBuilder::new("boids", "Ivaylogi").conf(conf.clone()).build().unwrap();
+    let (mut ctx, mut event_loop) = ContextBuilder::new("boids", "Ivaylogi").conf(conf).build().unwrap();
 
     if let Ok(manifest_dir) = env::var("CARGO_MANIFEST_DIR") {
         let mut path = path::PathBuf::from(manifest_dir);
@@ -485,7 +849,7 @@ pub fn main() {
         filesystem::mount(&mut ctx, &path, true);
     }
 
-    let mut state = MainState::new(&mut ctx, &conf).unwrap();
+    let mut state = MainState::new(&mut ctx, simulation, options, config, recorders, scene.spawn).unwrap();
 
     match event::run(&mut ctx, &mut event_loop, &mut state) {
         Err(e) => println!("ERROR in event::run -> {:?}", e),
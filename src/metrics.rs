@@ -0,0 +1,228 @@
+use ggez::nalgebra::{Point2, Vector2};
+
+use std::f32::consts::PI;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::grid::SpatialGrid;
+use crate::simulation::{Boundary, Simulation};
+use crate::tools::Tools;
+
+/// Order parameters describing the whole flock at one tick.
+///
+/// Distances are measured across the edges of the world when the boundary
+/// wraps. An empty flock measures zero everywhere.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct FlockMetrics {
+    pub birds: usize,
+    /// Length of the mean unit heading: 1 when every bird flies the same way,
+    /// near 0 when the headings cancel out.
+    pub polarization: f32,
+    /// Length of the mean angular momentum of unit headings around `center`:
+    /// 1 when the flock circles its centre, near 0 otherwise.
+    pub milling: f32,
+    /// Mean distance from each bird to its closest neighbour.
+    pub mean_nearest_neighbour_distance: f32,
+    /// Mean distance of the birds from `center`.
+    pub radius: f32,
+    pub mean_speed: f32,
+    /// Centre of mass of the flock.
+    pub center: Point2<f32>,
+}
+
+impl FlockMetrics {
+    /// Names of the fields, in the order `MetricsLog` writes them.
+    pub const COLUMNS: &'static [&'static str] = &[
+        "birds", "polarization", "milling", "mean_nearest_neighbour_distance", "radius", "mean_speed", "center_x", "center_y"
+    ];
+
+    /// Measures the current state of `simulation`'s birds.
+    pub fn measure(simulation: &Simulation) -> FlockMetrics {
+        let birds = &simulation.birds;
+        let mut metrics = FlockMetrics {
+            birds: birds.len(),
+            polarization: 0.0,
+            milling: 0.0,
+            mean_nearest_neighbour_distance: 0.0,
+            radius: 0.0,
+            mean_speed: 0.0,
+            center: Point2::new(simulation.width / 2.0, simulation.height / 2.0),
+        };
+        if birds.is_empty() {
+            return metrics;
+        }
+        let count = birds.len() as f32;
+        metrics.center = center(simulation);
+
+        let mut heading_sum: Vector2<f32> = Vector2::new(0.0, 0.0);
+        let mut angular_momentum_sum = 0.0;
+        let mut speed_sum = 0.0;
+        let mut radius_sum = 0.0;
+        for bird in birds.iter() {
+            let speed = Tools::vector_length(&bird.vel);
+            speed_sum += speed;
+            let heading = if speed > 0.0 { bird.vel / speed } else { bird.vel };
+            heading_sum += heading;
+
+            let offset = simulation.offset(metrics.center, bird.pos);
+            let distance = Tools::vector_length(&offset);
+            radius_sum += distance;
+            if distance > 0.0 {
+                angular_momentum_sum += (offset.x * heading.y - offset.y * heading.x) / distance;
+            }
+        }
+        metrics.polarization = Tools::vector_length(&heading_sum) / count;
+        metrics.milling = angular_momentum_sum.abs() / count;
+        metrics.mean_speed = speed_sum / count;
+        metrics.radius = radius_sum / count;
+        metrics.mean_nearest_neighbour_distance = mean_nearest_neighbour_distance(simulation);
+        metrics
+    }
+}
+
+impl fmt::Display for FlockMetrics {
+    /// One `Name:value` line per metric, as drawn on the HUD.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Polarization:{:.3}", self.polarization)?;
+        writeln!(f, "Milling:{:.3}", self.milling)?;
+        writeln!(f, "Nearest neighbour:{:.1}", self.mean_nearest_neighbour_distance)?;
+        writeln!(f, "Radius:{:.1}", self.radius)?;
+        write!(f, "Mean speed:{:.2}", self.mean_speed)
+    }
+}
+
+/// Appends `FlockMetrics` to a CSV file every `interval` ticks.
+///
+/// Each row holds the tick, time and seed, the metrics, and one 0/1 column per
+/// steering rule telling whether it was enabled. The header is written with
+/// the first row, naming the rules in the simulation's order.
+pub struct MetricsLog<W: Write> {
+    writer: W,
+    interval: u64,
+    header_written: bool,
+}
+
+impl MetricsLog<BufWriter<File>> {
+    /// Creates or truncates the file at `path`.
+    pub fn create<P: AsRef<Path>>(path: P, interval: u64) -> io::Result<Self> {
+        Ok(MetricsLog::new(BufWriter::new(File::create(path)?), interval))
+    }
+}
+
+impl<W: Write> MetricsLog<W> {
+    /// An `interval` of 0 is treated as 1, logging every tick.
+    pub fn new(writer: W, interval: u64) -> Self {
+        MetricsLog {
+            writer,
+            interval: interval.max(1),
+            header_written: false,
+        }
+    }
+
+    /// Writes a row for the current tick if it is a multiple of `interval`,
+    /// flushing it so the file can be read while the simulation runs.
+    pub fn record(&mut self, simulation: &Simulation) -> io::Result<()> {
+        if !simulation.tick.is_multiple_of(self.interval) {
+            return Ok(());
+        }
+        if !self.header_written {
+            let rules = simulation.rules.iter().map(|active| active.rule.name());
+            let header: Vec<&str> = ["tick", "time", "seed"].iter()
+                .chain(FlockMetrics::COLUMNS.iter())
+                .cloned()
+                .chain(rules)
+                .collect();
+            writeln!(self.writer, "{}", header.join(","))?;
+            self.header_written = true;
+        }
+
+        let metrics = simulation.metrics();
+        write!(
+            self.writer,
+            "{},{},{},{},{},{},{},{},{},{},{}",
+            simulation.tick,
+            simulation.time,
+            simulation.seed,
+            metrics.birds,
+            metrics.polarization,
+            metrics.milling,
+            metrics.mean_nearest_neighbour_distance,
+            metrics.radius,
+            metrics.mean_speed,
+            metrics.center.x,
+            metrics.center.y
+        )?;
+        for active in simulation.rules.iter() {
+            write!(self.writer, ",{}", active.enabled as u8)?;
+        }
+        writeln!(self.writer)?;
+        self.writer.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Centre of mass of the birds. With a wrapping boundary each axis is treated
+/// as a circle and averaged as angles, so a flock straddling an edge is
+/// centred on the edge rather than in the middle of the world.
+fn center(simulation: &Simulation) -> Point2<f32> {
+    let count = simulation.birds.len() as f32;
+    if simulation.parameters.boundary != Boundary::Wrap {
+        let sum = simulation.birds.iter().fold(Vector2::new(0.0, 0.0), |sum, bird| sum + bird.pos.coords);
+        return Point2::from(sum / count);
+    }
+
+    let circular_mean = |values: &mut dyn Iterator<Item = f32>, length: f32| {
+        let (cos_sum, sin_sum) = values
+            .map(|value| value / length * 2.0 * PI)
+            .fold((0.0, 0.0), |(cos_sum, sin_sum): (f32, f32), angle| (cos_sum + angle.cos(), sin_sum + angle.sin()));
+        let angle = sin_sum.atan2(cos_sum);
+        (if angle < 0.0 { angle + 2.0 * PI } else { angle }) / (2.0 * PI) * length
+    };
+    Point2::new(
+        circular_mean(&mut simulation.birds.iter().map(|bird| bird.pos.x), simulation.width),
+        circular_mean(&mut simulation.birds.iter().map(|bird| bird.pos.y), simulation.height),
+    )
+}
+
+/// Mean distance to the closest other bird, or 0 for fewer than two birds.
+///
+/// Uses its own grid, searching a doubling radius around each bird until a
+/// neighbour is found inside it.
+fn mean_nearest_neighbour_distance(simulation: &Simulation) -> f32 {
+    let birds = &simulation.birds;
+    if birds.len() < 2 {
+        return 0.0;
+    }
+    let cell_size = simulation.max_view_distance();
+    let mut grid = SpatialGrid::new(simulation.width, simulation.height, cell_size);
+    grid.rebuild(birds.iter().map(|bird| bird.pos));
+
+    let mut candidates = Vec::new();
+    let mut distance_sum = 0.0;
+    for (i, bird) in birds.iter().enumerate() {
+        let mut radius = cell_size;
+        let nearest = loop {
+            if simulation.parameters.boundary == Boundary::Wrap {
+                grid.query_wrapped(bird.pos, radius, simulation.width, simulation.height, &mut candidates);
+            }
+            else {
+                grid.query(bird.pos, radius, &mut candidates);
+            }
+            let nearest = candidates.iter()
+                .filter(|&&j| j != i)
+                .map(|&j| simulation.distance(bird.pos, birds[j].pos))
+                .fold(f32::INFINITY, f32::min);
+            if nearest <= radius || candidates.len() == birds.len() {
+                break nearest;
+            }
+            radius *= 2.0;
+        };
+        distance_sum += nearest;
+    }
+    distance_sum / birds.len() as f32
+}
@@ -0,0 +1,124 @@
+use ggez::{Context, GameResult};
+use ggez::graphics;
+use ggez::graphics::{DrawMode, MeshBuilder};
+use ggez::nalgebra::Point2;
+
+use crate::camera::Camera;
+use crate::simulation::Simulation;
+
+/// A small view of the whole world in the bottom right corner of the window,
+/// shading each cell by how many birds are in it and outlining the part of
+/// the world the camera shows.
+pub struct Minimap {
+    pub visible: bool,
+}
+
+impl Default for Minimap {
+    fn default() -> Self {
+        Minimap::new()
+    }
+}
+
+impl Minimap {
+    /// Length in pixels of the longer side.
+    pub const SIZE: f32 = 200.0;
+    pub const MARGIN: f32 = 10.0;
+    /// Number of density cells along the longer side.
+    pub const CELLS: usize = 40;
+
+    pub fn new() -> Self {
+        Minimap {
+            visible: true,
+        }
+    }
+
+    /// Where the minimap is drawn, in screen coordinates, keeping the
+    /// proportions of the world.
+    pub fn rect(&self, camera: &Camera, simulation: &Simulation) -> graphics::Rect {
+        let scale = Minimap::SIZE / simulation.width.max(simulation.height);
+        let (width, height) = (simulation.width * scale, simulation.height * scale);
+        let (screen_width, screen_height) = camera.screen_size();
+        graphics::Rect::new(screen_width - width - Minimap::MARGIN, screen_height - height - Minimap::MARGIN, width, height)
+    }
+
+    /// Whether `point` is over the visible minimap, so clicks there should not
+    /// reach the world behind it.
+    pub fn contains(&self, camera: &Camera, simulation: &Simulation, point: Point2<f32>) -> bool {
+        let rect = self.rect(camera, simulation);
+        self.visible && point.x >= rect.x && point.x <= rect.x + rect.w && point.y >= rect.y && point.y <= rect.y + rect.h
+    }
+
+    /// The world point under the screen point `point` of the minimap.
+    pub fn minimap_to_world(&self, camera: &Camera, simulation: &Simulation, point: Point2<f32>) -> Point2<f32> {
+        let rect = self.rect(camera, simulation);
+        Point2::new(
+            (point.x - rect.x) / rect.w * simulation.width,
+            (point.y - rect.y) / rect.h * simulation.height)
+    }
+
+    /// The screen point of the minimap showing the world point `point`.
+    pub fn world_to_minimap(&self, camera: &Camera, simulation: &Simulation, point: Point2<f32>) -> Point2<f32> {
+        let rect = self.rect(camera, simulation);
+        Point2::new(
+            rect.x + point.x / simulation.width * rect.w,
+            rect.y + point.y / simulation.height * rect.h)
+    }
+
+    /// Number of columns and rows of density cells, `CELLS` along the longer
+    /// side of the world and at least one along the shorter.
+    pub fn cells(simulation: &Simulation) -> (usize, usize) {
+        let cell_size = simulation.width.max(simulation.height) / Minimap::CELLS as f32;
+        (
+            ((simulation.width / cell_size).round() as usize).max(1),
+            ((simulation.height / cell_size).round() as usize).max(1)
+        )
+    }
+
+    /// Number of birds in each cell of a `columns` by `rows` grid over the
+    /// world, row by row. Birds outside the world count in the nearest cell.
+    pub fn density(simulation: &Simulation, columns: usize, rows: usize) -> Vec<usize> {
+        let mut counts = vec![0; columns * rows];
+        for bird in simulation.birds.iter() {
+            let column = ((bird.pos.x / simulation.width * columns as f32).max(0.0) as usize).min(columns - 1);
+            let row = ((bird.pos.y / simulation.height * rows as f32).max(0.0) as usize).min(rows - 1);
+            counts[row * columns + column] += 1;
+        }
+        counts
+    }
+
+    /// Draws the minimap. Expects the screen coordinates to be set to the window.
+    pub fn draw(&self, ctx: &mut Context, simulation: &Simulation, camera: &Camera) -> GameResult<()> {
+        if !self.visible {
+            return Ok(());
+        }
+        let rect = self.rect(camera, simulation);
+        let mut builder = MeshBuilder::new();
+        builder.rectangle(DrawMode::fill(), rect, (0, 0, 0, 150).into());
+
+        let (columns, rows) = Minimap::cells(simulation);
+        let counts = Minimap::density(simulation, columns, rows);
+        let max_count = counts.iter().cloned().max().unwrap_or(0);
+        let (cell_width, cell_height) = (rect.w / columns as f32, rect.h / rows as f32);
+        for (i, &count) in counts.iter().enumerate().filter(|&(_, &count)| count > 0) {
+            let alpha = 60 + (195 * count / max_count) as u8;
+            builder.rectangle(
+                DrawMode::fill(),
+                graphics::Rect::new(rect.x + (i % columns) as f32 * cell_width, rect.y + (i / columns) as f32 * cell_height, cell_width, cell_height),
+                (0, 255, 0, alpha).into());
+        }
+
+        // the part of the world in view, clipped to the minimap
+        let view = camera.world_rect();
+        let top_left = self.world_to_minimap(camera, simulation, Point2::new(view.x, view.y));
+        let bottom_right = self.world_to_minimap(camera, simulation, Point2::new(view.x + view.w, view.y + view.h));
+        let (left, top) = (top_left.x.max(rect.x), top_left.y.max(rect.y));
+        let (right, bottom) = (bottom_right.x.min(rect.x + rect.w), bottom_right.y.min(rect.y + rect.h));
+        if right > left && bottom > top {
+            builder.rectangle(DrawMode::stroke(1.0), graphics::Rect::new(left, top, right - left, bottom - top), (255, 255, 255).into());
+        }
+        builder.rectangle(DrawMode::stroke(1.0), rect, (150, 150, 170).into());
+
+        let mesh = builder.build(ctx)?;
+        graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+    }
+}
@@ -0,0 +1,243 @@
+use ggez::{Context, GameResult};
+use ggez::graphics;
+use ggez::graphics::{DrawMode, MeshBuilder};
+use ggez::nalgebra::Point2;
+
+use crate::simulation::{Parameters, Simulation};
+
+/// One editable value of `Parameters`, limited to `min..=max` and rounded to
+/// multiples of `step`.
+#[derive(Debug, Copy, Clone)]
+pub struct Slider {
+    pub label: &'static str,
+    pub min: f32,
+    pub max: f32,
+    pub step: f32,
+    get: fn(&Parameters) -> f32,
+    set: fn(&mut Parameters, f32),
+}
+
+impl Slider {
+    pub fn value(&self, parameters: &Parameters) -> f32 {
+        (self.get)(parameters)
+    }
+
+    /// Sets the value, clamped to the range and rounded to the nearest step.
+    pub fn set_value(&self, parameters: &mut Parameters, value: f32) {
+        let value = ((value.clamp(self.min, self.max) - self.min) / self.step).round() * self.step + self.min;
+        (self.set)(parameters, value.min(self.max));
+    }
+
+    /// Sets the value in the simulation's parameters, clamped and rounded like
+    /// `set_value`. Species with their own parameters follow it unless they
+    /// set this value to something else than the old one.
+    pub fn apply(&self, simulation: &mut Simulation, value: f32) {
+        let old_value = self.value(&simulation.parameters);
+        self.set_value(&mut simulation.parameters, value);
+        let value = self.value(&simulation.parameters);
+        for species in simulation.species.iter_mut() {
+            if let Some(parameters) = species.parameters.as_mut() {
+                if self.value(parameters) == old_value {
+                    (self.set)(parameters, value);
+                }
+            }
+        }
+    }
+
+    /// Where the value lies in the range, from 0 at `min` to 1 at `max`.
+    pub fn fraction(&self, parameters: &Parameters) -> f32 {
+        ((self.value(parameters) - self.min) / (self.max - self.min)).clamp(0.0, 1.0)
+    }
+
+    /// The value formatted with as many decimals as the step needs.
+    pub fn format(&self, parameters: &Parameters) -> String {
+        let decimals = (-self.step.log10()).ceil().max(0.0) as usize;
+        format!("{:.*}", decimals, self.value(parameters))
+    }
+}
+
+macro_rules! slider {
+    ($field:ident, $label:expr, $min:expr, $max:expr, $step:expr) => {
+        Slider {
+            label: $label,
+            min: $min,
+            max: $max,
+            step: $step,
+            get: |parameters| parameters.$field,
+            set: |parameters, value| parameters.$field = value,
+        }
+    };
+}
+
+/// The steering weights, distances, radii and speed limits shown on the panel.
+/// Every value in their ranges passes `Config::validate`.
+pub const SLIDERS: &[Slider] = &[
+    slider!(alignment_modifier, "Alignment weight", 0.0, 5.0, 0.1),
+    slider!(separation_modifier, "Separation weight", 0.0, 5.0, 0.1),
+    slider!(cohesion_modifier, "Cohesion weight", 0.0, 5.0, 0.1),
+    slider!(obstacle_modifier, "Evasion weight", 0.0, 10.0, 0.1),
+    slider!(flee_modifier, "Flee weight", 0.0, 10.0, 0.1),
+    slider!(boundary_modifier, "Boundary weight", 0.0, 10.0, 0.1),
+    slider!(random_movement, "Random movement", 0.0, 1.0, 0.01),
+    slider!(alignment_view_distance, "Alignment distance", 5.0, 300.0, 5.0),
+    slider!(separation_view_distance, "Separation distance", 5.0, 300.0, 5.0),
+    slider!(cohesion_view_distance, "Cohesion distance", 5.0, 300.0, 5.0),
+    slider!(obstacle_radius, "Obstacle radius", 5.0, 200.0, 5.0),
+    slider!(look_ahead_distance, "Look-ahead distance", 10.0, 400.0, 5.0),
+    slider!(fear_radius, "Fear radius", 5.0, 400.0, 5.0),
+    slider!(boundary_margin, "Boundary margin", 5.0, 300.0, 5.0),
+    slider!(max_speed, "Max speed", 0.5, 10.0, 0.1),
+    slider!(max_steering_velocity, "Max steering", 0.0, 1.0, 0.01),
+    slider!(predator_max_speed, "Predator max speed", 0.5, 10.0, 0.1),
+    slider!(predator_max_steering_velocity, "Predator max steering", 0.0, 1.0, 0.01),
+    slider!(predator_view_distance, "Predator view distance", 10.0, 800.0, 10.0),
+    slider!(predator_kill_radius, "Predator kill radius", 0.0, 50.0, 1.0),
+];
+
+/// An overlay of `SLIDERS` that edits the simulation's `Parameters` with the
+/// mouse: click or drag along a track to set a value, or scroll over a row to
+/// change it one step at a time.
+///
+/// It shows the simulation's own values. A change is passed on to the species
+/// that do not set the value themselves, see `Slider::apply`.
+pub struct ParameterPanel {
+    /// Top left corner in screen coordinates.
+    pub pos: Point2<f32>,
+    pub visible: bool,
+    /// Index of the slider being dragged.
+    dragging: Option<usize>,
+}
+
+impl ParameterPanel {
+    pub const ROW_HEIGHT: f32 = 20.0;
+    pub const LABEL_WIDTH: f32 = 180.0;
+    pub const TRACK_WIDTH: f32 = 120.0;
+    pub const VALUE_WIDTH: f32 = 60.0;
+    pub const PADDING: f32 = 10.0;
+
+    pub fn new(pos: Point2<f32>) -> Self {
+        ParameterPanel {
+            pos,
+            visible: false,
+            dragging: None,
+        }
+    }
+
+    pub fn width() -> f32 {
+        ParameterPanel::LABEL_WIDTH + ParameterPanel::TRACK_WIDTH + ParameterPanel::VALUE_WIDTH + 2.0 * ParameterPanel::PADDING
+    }
+
+    pub fn height() -> f32 {
+        SLIDERS.len() as f32 * ParameterPanel::ROW_HEIGHT + 2.0 * ParameterPanel::PADDING
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.dragging.is_some()
+    }
+
+    /// Whether `point` is over the visible panel, so clicks there should not
+    /// reach the world behind it.
+    pub fn contains(&self, point: Point2<f32>) -> bool {
+        self.visible
+            && point.x >= self.pos.x && point.x < self.pos.x + ParameterPanel::width()
+            && point.y >= self.pos.y && point.y < self.pos.y + ParameterPanel::height()
+    }
+
+    /// Index of the slider in the row under `point`.
+    pub fn row_at(&self, point: Point2<f32>) -> Option<usize> {
+        if !self.contains(point) {
+            return None;
+        }
+        let row = ((point.y - self.pos.y - ParameterPanel::PADDING) / ParameterPanel::ROW_HEIGHT).floor();
+        if row >= 0.0 && (row as usize) < SLIDERS.len() { Some(row as usize) } else { None }
+    }
+
+    /// Starts dragging the slider whose track is under `point` and jumps it
+    /// there. Returns whether the click landed on the panel.
+    pub fn mouse_down(&mut self, point: Point2<f32>, simulation: &mut Simulation) -> bool {
+        if !self.contains(point) {
+            return false;
+        }
+        if let Some(row) = self.row_at(point) {
+            let track_x = self.track_x();
+            if point.x >= track_x && point.x <= track_x + ParameterPanel::TRACK_WIDTH {
+                self.dragging = Some(row);
+                self.drag(point, simulation);
+            }
+        }
+        true
+    }
+
+    /// Moves the slider being dragged, if any, to follow `point`.
+    pub fn mouse_motion(&mut self, point: Point2<f32>, simulation: &mut Simulation) {
+        if self.dragging.is_some() {
+            self.drag(point, simulation);
+        }
+    }
+
+    /// Stops dragging. Returns whether a slider was being dragged.
+    pub fn mouse_up(&mut self) -> bool {
+        self.dragging.take().is_some()
+    }
+
+    /// Changes the slider under `point` by `steps` steps. Returns whether
+    /// `point` was over the panel.
+    pub fn scroll(&mut self, point: Point2<f32>, steps: f32, simulation: &mut Simulation) -> bool {
+        if let Some(row) = self.row_at(point) {
+            let slider = &SLIDERS[row];
+            slider.apply(simulation, slider.value(&simulation.parameters) + steps * slider.step);
+        }
+        self.contains(point)
+    }
+
+    pub fn draw(&self, ctx: &mut Context, parameters: &Parameters) -> GameResult<()> {
+        if !self.visible {
+            return Ok(());
+        }
+        let track_x = self.track_x();
+        let mut builder = MeshBuilder::new();
+        builder.rectangle(
+            DrawMode::fill(),
+            graphics::Rect::new(self.pos.x, self.pos.y, ParameterPanel::width(), ParameterPanel::height()),
+            (0, 0, 0, 150).into());
+        for (i, slider) in SLIDERS.iter().enumerate() {
+            let center_y = self.row_y(i) + ParameterPanel::ROW_HEIGHT / 2.0;
+            builder.rectangle(
+                DrawMode::fill(),
+                graphics::Rect::new(track_x, center_y - 1.0, ParameterPanel::TRACK_WIDTH, 2.0),
+                (150, 150, 170).into());
+            let handle_color = if self.dragging == Some(i) { (255, 225, 0) } else { (255, 255, 255) };
+            builder.rectangle(
+                DrawMode::fill(),
+                graphics::Rect::new(track_x + slider.fraction(parameters) * ParameterPanel::TRACK_WIDTH - 3.0, center_y - 6.0, 6.0, 12.0),
+                handle_color.into());
+        }
+        let mesh = builder.build(ctx)?;
+        graphics::draw(ctx, &mesh, graphics::DrawParam::default())?;
+
+        let drawparams = graphics::DrawParam::new().color((255, 255, 255).into());
+        for (i, slider) in SLIDERS.iter().enumerate() {
+            let y = self.row_y(i) + 3.0;
+            graphics::draw(ctx, &graphics::Text::new(slider.label), drawparams.dest(Point2::new(self.pos.x + ParameterPanel::PADDING, y)))?;
+            let value_x = track_x + ParameterPanel::TRACK_WIDTH + ParameterPanel::PADDING;
+            graphics::draw(ctx, &graphics::Text::new(slider.format(parameters)), drawparams.dest(Point2::new(value_x, y)))?;
+        }
+        Ok(())
+    }
+
+    fn drag(&self, point: Point2<f32>, simulation: &mut Simulation) {
+        if let Some(row) = self.dragging {
+            let slider = &SLIDERS[row];
+            let fraction = (point.x - self.track_x()) / ParameterPanel::TRACK_WIDTH;
+            slider.apply(simulation, slider.min + fraction * (slider.max - slider.min));
+        }
+    }
+
+    fn track_x(&self) -> f32 {
+        self.pos.x + ParameterPanel::PADDING + ParameterPanel::LABEL_WIDTH
+    }
+
+    fn row_y(&self, row: usize) -> f32 {
+        self.pos.y + ParameterPanel::PADDING + row as f32 * ParameterPanel::ROW_HEIGHT
+    }
+}
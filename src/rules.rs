@@ -0,0 +1,516 @@
+use ggez::nalgebra::{distance, Point2, Vector2};
+use rand::{Rng, RngCore};
+use serde::Deserialize;
+
+use crate::entities::{Bird, Obstacle, Predator};
+use crate::simulation::{Boundary, Neighbourhood, ObstacleAvoidance, Parameters};
+use crate::species::{Interaction, Species};
+use crate::tools::Tools;
+
+/// Read-only view of the flock that steering rules are evaluated against.
+///
+/// `birds` holds the state the rules should read: the previous tick in
+/// double-buffered mode, the partially updated flock in sequential mode.
+pub struct World<'a> {
+    pub birds: &'a [Bird],
+    pub obstacles: &'a [Obstacle],
+    pub predators: &'a [Predator],
+    pub species: &'a [Species],
+    pub parameters: &'a Parameters,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl<'a> World<'a> {
+    /// The constants that apply to `bird`: its species' own, or `parameters`.
+    pub fn parameters_of(&self, bird: &Bird) -> &Parameters {
+        self.species[bird.species].parameters_or(self.parameters)
+    }
+
+    /// The shortest vector from `from` to `to`, across the edges of the world
+    /// when the boundary wraps.
+    pub fn offset(&self, from: Point2<f32>, to: Point2<f32>) -> Vector2<f32> {
+        if self.parameters.boundary == Boundary::Wrap {
+            Tools::wrapped_offset(from, to, self.width, self.height)
+        }
+        else {
+            to - from
+        }
+    }
+
+    /// Length of `offset(a, b)`.
+    pub fn distance(&self, a: Point2<f32>, b: Point2<f32>) -> f32 {
+        Tools::vector_length(&self.offset(a, b))
+    }
+
+    /// Whether a neighbour `distance` away is seen by a rule with the given
+    /// view distance. Always true in topological mode, where the neighbour
+    /// list only holds the nearest birds.
+    pub fn within_view(&self, distance: f32, view_distance: f32) -> bool {
+        self.parameters.neighbourhood == Neighbourhood::Topological || distance <= view_distance
+    }
+
+    /// How `bird` reacts to `other`, given their species.
+    pub fn interaction(&self, bird: &Bird, other: &Bird) -> Interaction {
+        self.species[bird.species].interaction(other.species)
+    }
+}
+
+/// One component of a bird's steering.
+///
+/// `neighbours` are indices into `world.birds` of every bird within
+/// `Simulation::max_view_distance` of `bird`, possibly including `bird` itself
+/// and possibly a few more; rules still apply their own distance and
+/// `Interaction` checks. With `Neighbourhood::Topological` they are exactly
+/// the nearest birds instead, and distances are checked with `World::within_view`.
+/// `rng` is private to this bird and tick, so rules may draw from it freely.
+pub trait SteeringRule: Send + Sync {
+    /// Name shown on the HUD and used to toggle the rule.
+    fn name(&self) -> &str;
+    /// Colour of the rule's debug vector and HUD text.
+    fn color(&self) -> (u8, u8, u8);
+    fn steer(&self, bird: &Bird, neighbours: &[usize], world: &World, rng: &mut dyn RngCore) -> Vector2<f32>;
+}
+
+/// A steering rule in the simulation's ordered rule list.
+pub struct ActiveRule {
+    pub rule: Box<dyn SteeringRule>,
+    pub enabled: bool,
+}
+
+/// Which built-in rules are used, in what order and whether they start enabled.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Rules {
+    pub order: Vec<String>,
+    pub separation: bool,
+    pub alignment: bool,
+    pub cohesion: bool,
+    pub random_movement: bool,
+    pub obstacle_evasion: bool,
+    pub flee: bool,
+    pub containment: bool,
+}
+
+impl Rules {
+    pub const NAMES: &'static [&'static str] = &["alignment", "separation", "cohesion", "random_movement", "obstacle_evasion", "flee", "containment"];
+
+    /// Creates the built-in rule with the given name.
+    pub fn builtin(name: &str) -> Option<Box<dyn SteeringRule>> {
+        match name {
+            "alignment" => Some(Box::new(Alignment)),
+            "separation" => Some(Box::new(Separation)),
+            "cohesion" => Some(Box::new(Cohesion)),
+            "random_movement" => Some(Box::new(RandomMovement)),
+            "obstacle_evasion" => Some(Box::new(ObstacleEvasion)),
+            "flee" => Some(Box::new(Flee)),
+            "containment" => Some(Box::new(Containment)),
+            _ => None
+        }
+    }
+
+    /// Whether the built-in rule `name` starts enabled.
+    pub fn is_enabled(&self, name: &str) -> bool {
+        match name {
+            "alignment" => self.alignment,
+            "separation" => self.separation,
+            "cohesion" => self.cohesion,
+            "random_movement" => self.random_movement,
+            "obstacle_evasion" => self.obstacle_evasion,
+            "flee" => self.flee,
+            "containment" => self.containment,
+            _ => true
+        }
+    }
+
+    /// Instantiates the rules listed in `order`. Unknown names are skipped.
+    pub fn build(&self) -> Vec<ActiveRule> {
+        self.order.iter()
+            .filter_map(|name| Rules::builtin(name).map(|rule| ActiveRule { rule, enabled: self.is_enabled(name) }))
+            .collect()
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            order: Rules::NAMES.iter().map(|name| name.to_string()).collect(),
+            separation: true,
+            alignment: true,
+            cohesion: true,
+            random_movement: true,
+            obstacle_evasion: true,
+            flee: true,
+            containment: true,
+        }
+    }
+}
+
+/// Steer towards the average heading of nearby birds.
+pub struct Alignment;
+
+impl SteeringRule for Alignment {
+    fn name(&self) -> &str {
+        "alignment"
+    }
+
+    fn color(&self) -> (u8, u8, u8) {
+        (255, 0, 0)
+    }
+
+    fn steer(&self, bird: &Bird, neighbours: &[usize], world: &World, _rng: &mut dyn RngCore) -> Vector2<f32> {
+        let parameters = world.parameters_of(bird);
+        let mut velocity_sum_of_neigbours: Vector2<f32> = Vector2::new(0.0, 0.0);
+        let mut number_of_neighbours = 0;
+
+        for &j in neighbours {
+            if world.interaction(bird, &world.birds[j]) < Interaction::Align {
+                continue;
+            }
+            let offset: Vector2<f32> = world.offset(bird.pos, world.birds[j].pos);
+            let distance: f32 = Tools::vector_length(&offset);
+            if distance > 0.0 && world.within_view(distance, parameters.alignment_view_distance)
+                && Tools::in_field_of_view(&bird.vel, &offset, parameters.alignment_field_of_view) {
+                velocity_sum_of_neigbours += world.birds[j].vel;
+                number_of_neighbours += 1;
+            }
+        }
+
+        if number_of_neighbours > 0 && Tools::vector_length(&velocity_sum_of_neigbours) > 0.0 {
+            velocity_sum_of_neigbours /= number_of_neighbours as f32;
+            Tools::normalize_vector(&mut velocity_sum_of_neigbours);
+            velocity_sum_of_neigbours *= parameters.max_speed;
+            velocity_sum_of_neigbours -= bird.vel;
+            Tools::limit_vector(&mut velocity_sum_of_neigbours, parameters.max_steering_velocity);
+        }
+        velocity_sum_of_neigbours * parameters.alignment_modifier
+    }
+}
+
+/// Steer away from birds that are too close.
+pub struct Separation;
+
+impl SteeringRule for Separation {
+    fn name(&self) -> &str {
+        "separation"
+    }
+
+    fn color(&self) -> (u8, u8, u8) {
+        (0, 255, 0)
+    }
+
+    fn steer(&self, bird: &Bird, neighbours: &[usize], world: &World, _rng: &mut dyn RngCore) -> Vector2<f32> {
+        let parameters = world.parameters_of(bird);
+        let mut steer_away_velocity: Vector2<f32> = Vector2::new(0.0, 0.0);
+        let mut number_of_neighbours = 0;
+
+        for &j in neighbours {
+            if world.interaction(bird, &world.birds[j]) == Interaction::Ignore {
+                continue;
+            }
+            let mut vector_away_from_neightbour: Vector2<f32> = world.offset(world.birds[j].pos, bird.pos);
+            let distance: f32 = Tools::vector_length(&vector_away_from_neightbour);
+            if distance > 0.0 && world.within_view(distance, parameters.separation_view_distance)
+                && Tools::in_field_of_view(&bird.vel, &-vector_away_from_neightbour, parameters.separation_field_of_view) {
+                Tools::normalize_vector(&mut vector_away_from_neightbour);
+                vector_away_from_neightbour /= distance;
+                steer_away_velocity += vector_away_from_neightbour;
+                number_of_neighbours += 1;
+            }
+        }
+
+        if number_of_neighbours > 0 {
+            steer_away_velocity /= number_of_neighbours as f32;
+        }
+        if Tools::vector_length(&steer_away_velocity) > 0.0 {
+            Tools::normalize_vector(&mut steer_away_velocity);
+            steer_away_velocity *= parameters.max_speed;
+            steer_away_velocity -= bird.vel;
+            Tools::limit_vector(&mut steer_away_velocity, parameters.max_steering_velocity);
+        }
+        steer_away_velocity * parameters.separation_modifier
+    }
+}
+
+/// Steer towards the centre of mass of nearby birds.
+pub struct Cohesion;
+
+impl SteeringRule for Cohesion {
+    fn name(&self) -> &str {
+        "cohesion"
+    }
+
+    fn color(&self) -> (u8, u8, u8) {
+        (0, 0, 255)
+    }
+
+    fn steer(&self, bird: &Bird, neighbours: &[usize], world: &World, _rng: &mut dyn RngCore) -> Vector2<f32> {
+        let parameters = world.parameters_of(bird);
+        // relative to `bird`, so the average is right across a wrapping edge
+        let mut offset_sum: Vector2<f32> = Vector2::new(0.0, 0.0);
+        let mut number_of_neighbours = 0;
+        let mut steer_towards_velocity: Vector2<f32> = Vector2::new(0.0, 0.0);
+
+        for &j in neighbours {
+            if world.interaction(bird, &world.birds[j]) < Interaction::Cohere {
+                continue;
+            }
+            let offset: Vector2<f32> = world.offset(bird.pos, world.birds[j].pos);
+            let distance: f32 = Tools::vector_length(&offset);
+            if distance > 0.0 && world.within_view(distance, parameters.cohesion_view_distance)
+                && Tools::in_field_of_view(&bird.vel, &offset, parameters.cohesion_field_of_view) {
+                offset_sum += offset;
+                number_of_neighbours += 1;
+            }
+        }
+
+        if number_of_neighbours > 0 {
+            let mut vector_towards_average: Vector2<f32> = offset_sum / number_of_neighbours as f32;
+            if Tools::vector_length(&vector_towards_average) > 0.0 {
+                Tools::normalize_vector(&mut vector_towards_average);
+                vector_towards_average *= parameters.max_speed;
+
+                steer_towards_velocity = vector_towards_average - bird.vel;
+                Tools::limit_vector(&mut steer_towards_velocity, parameters.max_steering_velocity);
+            }
+        }
+        steer_towards_velocity * parameters.cohesion_modifier
+    }
+}
+
+/// A small random nudge every tick.
+pub struct RandomMovement;
+
+impl SteeringRule for RandomMovement {
+    fn name(&self) -> &str {
+        "random_movement"
+    }
+
+    fn color(&self) -> (u8, u8, u8) {
+        (200, 200, 200)
+    }
+
+    fn steer(&self, bird: &Bird, _neighbours: &[usize], world: &World, rng: &mut dyn RngCore) -> Vector2<f32> {
+        let random_movement = world.parameters_of(bird).random_movement;
+        if random_movement <= 0.0 {
+            return Vector2::new(0.0, 0.0);
+        }
+        Vector2::new(
+            rng.gen_range(-random_movement .. random_movement),
+            rng.gen_range(-random_movement .. random_movement)
+        )
+    }
+}
+
+/// Steer clear of obstacles, either radially or by looking ahead, see `ObstacleAvoidance`.
+pub struct ObstacleEvasion;
+
+impl ObstacleEvasion {
+    /// Angle between the middle feeler and each side feeler.
+    pub const FEELER_ANGLE: f32 = std::f32::consts::PI / 6.0;
+    /// Length of the side feelers relative to the middle one.
+    pub const SIDE_FEELER_LENGTH: f32 = 0.6;
+
+    /// The feeler rays of `bird` as unit direction and length, middle one first.
+    /// Empty while the bird is not moving.
+    pub fn feelers(bird: &Bird, parameters: &Parameters) -> Vec<(Vector2<f32>, f32)> {
+        if Tools::vector_length(&bird.vel) == 0.0 {
+            return Vec::new();
+        }
+        let mut heading = bird.vel;
+        Tools::normalize_vector(&mut heading);
+        let rotate = |angle: f32| Vector2::new(
+            heading.x * angle.cos() - heading.y * angle.sin(),
+            heading.x * angle.sin() + heading.y * angle.cos());
+        let side_length = parameters.look_ahead_distance * ObstacleEvasion::SIDE_FEELER_LENGTH;
+        vec![
+            (heading, parameters.look_ahead_distance),
+            (rotate(-ObstacleEvasion::FEELER_ANGLE), side_length),
+            (rotate(ObstacleEvasion::FEELER_ANGLE), side_length)
+        ]
+    }
+
+    /// Away from the closest point of every obstacle within its `radius` of
+    /// the bird, at the obstacle's predicted position. Obstacles without a
+    /// radius of their own use `obstacle_radius`.
+    fn radial(bird: &Bird, world: &World) -> Vector2<f32> {
+        let parameters = world.parameters_of(bird);
+        let mut obstacle_evasion: Vector2<f32> = Vector2::new(0.0, 0.0);
+        let mut number_of_obstacles = 0;
+
+        for obstacle in world.obstacles.iter() {
+            let ahead = obstacle.predicted_pos();
+            let closest_point = obstacle.shape.closest_point(ahead, bird.pos);
+            let distance: f32 = distance(&bird.pos, &closest_point);
+            let inside = obstacle.shape.contains(ahead, bird.pos);
+            let in_view = Tools::in_field_of_view(&bird.vel, &(closest_point - bird.pos), parameters.obstacle_field_of_view);
+            if distance > 0.0 && ((distance <= obstacle.radius.unwrap_or(parameters.obstacle_radius) && in_view) || inside) {
+                let mut vector_away_from_obstacle: Vector2<f32> = bird.pos - closest_point;
+                Tools::normalize_vector(&mut vector_away_from_obstacle);
+                if inside {
+                    // inside a solid shape the way out is towards the outline
+                    vector_away_from_obstacle = -vector_away_from_obstacle;
+                }
+                vector_away_from_obstacle /= distance;
+                obstacle_evasion += vector_away_from_obstacle;
+                number_of_obstacles += 1;
+            }
+        }
+        if number_of_obstacles > 0 {
+            obstacle_evasion /= number_of_obstacles as f32;
+        }
+        if Tools::vector_length(&obstacle_evasion) > 0.0 {
+            Tools::normalize_vector(&mut obstacle_evasion);
+            obstacle_evasion *= parameters.max_speed;
+            obstacle_evasion -= bird.vel;
+            Tools::limit_vector(&mut obstacle_evasion, parameters.max_steering_velocity);
+        }
+        obstacle_evasion
+    }
+
+    /// Sideways around the nearest obstacle hit by a feeler, at its predicted
+    /// position, harder the closer the hit.
+    fn look_ahead(bird: &Bird, world: &World) -> Vector2<f32> {
+        let parameters = world.parameters_of(bird);
+        let feelers = ObstacleEvasion::feelers(bird, parameters);
+
+        // (fraction of the feeler length, surface normal, feeler direction)
+        let mut first_hit: Option<(f32, Vector2<f32>, Vector2<f32>)> = None;
+        for &(dir, length) in feelers.iter() {
+            for obstacle in world.obstacles.iter() {
+                if let Some((distance, normal)) = obstacle.shape.ray_cast(obstacle.predicted_pos(), bird.pos, dir, obstacle.radius.unwrap_or(parameters.obstacle_radius)) {
+                    let fraction = distance / length;
+                    if fraction <= 1.0 && first_hit.is_none_or(|(closest, _, _)| fraction < closest) {
+                        first_hit = Some((fraction, normal, dir));
+                    }
+                }
+            }
+        }
+
+        let mut obstacle_evasion: Vector2<f32> = Vector2::new(0.0, 0.0);
+        if let Some((fraction, normal, dir)) = first_hit {
+            let heading = feelers[0].0;
+            // the part of the normal across the heading says which way around is shorter
+            let mut around: Vector2<f32> = normal - heading * normal.dot(&heading);
+            if Tools::vector_length(&around) < 1e-3 {
+                // head-on, turn away from the side the feeler points to
+                around = Vector2::new(-dir.y, dir.x);
+                if around.dot(&(dir - heading)) > 0.0 {
+                    around = -around;
+                }
+            }
+            Tools::normalize_vector(&mut around);
+            obstacle_evasion = around * parameters.max_speed - bird.vel;
+            Tools::limit_vector(&mut obstacle_evasion, parameters.max_steering_velocity);
+            obstacle_evasion *= 1.0 - fraction;
+        }
+        obstacle_evasion
+    }
+}
+
+impl SteeringRule for ObstacleEvasion {
+    fn name(&self) -> &str {
+        "obstacle_evasion"
+    }
+
+    fn color(&self) -> (u8, u8, u8) {
+        (255, 225, 0)
+    }
+
+    fn steer(&self, bird: &Bird, _neighbours: &[usize], world: &World, _rng: &mut dyn RngCore) -> Vector2<f32> {
+        let parameters = world.parameters_of(bird);
+        let inside = world.obstacles.iter().any(|obstacle| obstacle.shape.contains(obstacle.predicted_pos(), bird.pos));
+        let obstacle_evasion = match parameters.obstacle_avoidance {
+            // feelers cast from inside a shape only see its outline, so push out radially instead
+            ObstacleAvoidance::LookAhead if !inside => ObstacleEvasion::look_ahead(bird, world),
+            _ => ObstacleEvasion::radial(bird, world)
+        };
+        obstacle_evasion * parameters.obstacle_modifier
+    }
+}
+
+/// Steer hard away from predators within `fear_radius`.
+pub struct Flee;
+
+impl SteeringRule for Flee {
+    fn name(&self) -> &str {
+        "flee"
+    }
+
+    fn color(&self) -> (u8, u8, u8) {
+        (255, 0, 255)
+    }
+
+    fn steer(&self, bird: &Bird, _neighbours: &[usize], world: &World, _rng: &mut dyn RngCore) -> Vector2<f32> {
+        let parameters = world.parameters_of(bird);
+        let mut flee: Vector2<f32> = Vector2::new(0.0, 0.0);
+
+        for predator in world.predators.iter() {
+            let mut vector_away_from_predator: Vector2<f32> = world.offset(predator.pos, bird.pos);
+            let distance: f32 = Tools::vector_length(&vector_away_from_predator);
+            if distance > 0.0 && distance <= parameters.fear_radius
+                && Tools::in_field_of_view(&bird.vel, &-vector_away_from_predator, parameters.flee_field_of_view) {
+                Tools::normalize_vector(&mut vector_away_from_predator);
+                vector_away_from_predator /= distance;
+                flee += vector_away_from_predator;
+            }
+        }
+        if Tools::vector_length(&flee) > 0.0 {
+            Tools::normalize_vector(&mut flee);
+            flee *= parameters.max_speed;
+            flee -= bird.vel;
+            Tools::limit_vector(&mut flee, parameters.max_steering_velocity);
+        }
+        flee * parameters.flee_modifier
+    }
+}
+
+/// Steer back towards the inside within `boundary_margin` of the world's edges.
+/// Only active with `Boundary::Contain`.
+pub struct Containment;
+
+impl SteeringRule for Containment {
+    fn name(&self) -> &str {
+        "containment"
+    }
+
+    fn color(&self) -> (u8, u8, u8) {
+        (0, 255, 255)
+    }
+
+    fn steer(&self, bird: &Bird, _neighbours: &[usize], world: &World, _rng: &mut dyn RngCore) -> Vector2<f32> {
+        let parameters = world.parameters_of(bird);
+        let mut containment: Vector2<f32> = Vector2::new(0.0, 0.0);
+        if world.parameters.boundary != Boundary::Contain {
+            return containment;
+        }
+
+        // keep the heading along the edge, but head inwards across it
+        let mut desired: Vector2<f32> = bird.vel;
+        let mut near_edge = false;
+        if bird.pos.x < parameters.boundary_margin {
+            desired.x = parameters.max_speed;
+            near_edge = true;
+        }
+        else if bird.pos.x > world.width - parameters.boundary_margin {
+            desired.x = -parameters.max_speed;
+            near_edge = true;
+        }
+        if bird.pos.y < parameters.boundary_margin {
+            desired.y = parameters.max_speed;
+            near_edge = true;
+        }
+        else if bird.pos.y > world.height - parameters.boundary_margin {
+            desired.y = -parameters.max_speed;
+            near_edge = true;
+        }
+
+        if near_edge {
+            Tools::normalize_vector(&mut desired);
+            desired *= parameters.max_speed;
+            containment = desired - bird.vel;
+            Tools::limit_vector(&mut containment, parameters.max_steering_velocity);
+        }
+        containment * parameters.boundary_modifier
+    }
+}
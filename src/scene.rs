@@ -0,0 +1,328 @@
+use ggez::nalgebra::{Point2, Vector2};
+use serde::{Deserialize, Serialize};
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::entities::{Motion, Obstacle, Shape};
+use crate::simulation::Simulation;
+use crate::snapshot::point;
+
+/// An authored starting layout read from a TOML file: the world size,
+/// obstacles and regions to spawn birds in.
+///
+/// Every section is optional:
+///
+/// ```toml
+/// [world]
+/// width = 1600.0
+/// height = 900.0
+///
+/// [[obstacles]]
+/// pos = [400.0, 300.0]
+///
+/// [[obstacles]]
+/// pos = [800.0, 450.0]
+/// radius = 30.0
+/// shape = { type = "rect", half_width = 100.0, half_height = 20.0 }
+/// motion = { type = "orbit", center = [800.0, 450.0], radius = 150.0, angular_speed = 0.01 }
+///
+/// [[spawn]]
+/// count = 100
+/// species = "starling"
+/// region = { type = "circle", center = [300.0, 300.0], radius = 100.0 }
+/// speed = [1.0, 2.0]
+/// heading = [0.0, 90.0]
+/// ```
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Scene {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub world: Option<WorldSize>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub obstacles: Vec<ObstacleScene>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub spawn: Vec<SpawnRegion>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorldSize {
+    pub width: f32,
+    pub height: f32,
+}
+
+/// One obstacle of a scene. Tables come last so the TOML can be written.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ObstacleScene {
+    #[serde(with = "point")]
+    pub pos: Point2<f32>,
+    /// Defaults to `parameters.obstacle_radius`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub radius: Option<f32>,
+    #[serde(default = "ObstacleScene::default_shape")]
+    pub shape: Shape,
+    #[serde(default = "ObstacleScene::default_motion")]
+    pub motion: Motion,
+}
+
+impl ObstacleScene {
+    fn default_shape() -> Shape {
+        Shape::Point
+    }
+
+    fn default_motion() -> Motion {
+        Motion::Static
+    }
+}
+
+/// `count` birds placed uniformly at random in `region`, flying at a speed
+/// between `speed[0]` and `speed[1]` in a direction between `heading[0]` and
+/// `heading[1]` degrees, clockwise from the positive x axis.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SpawnRegion {
+    pub count: usize,
+    /// Name of the species to spawn. Without one the birds take turns over
+    /// every species, like `Simulation::spawn_flock`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub species: Option<String>,
+    #[serde(default = "SpawnRegion::default_speed")]
+    pub speed: [f32; 2],
+    #[serde(default = "SpawnRegion::default_heading")]
+    pub heading: [f32; 2],
+    pub region: Region,
+}
+
+impl SpawnRegion {
+    fn default_speed() -> [f32; 2] {
+        [0.0, 0.1]
+    }
+
+    fn default_heading() -> [f32; 2] {
+        [0.0, 360.0]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Region {
+    /// The axis-aligned rectangle between the corners `min` and `max`.
+    Rect {
+        #[serde(with = "point")]
+        min: Point2<f32>,
+        #[serde(with = "point")]
+        max: Point2<f32>
+    },
+    Circle {
+        #[serde(with = "point")]
+        center: Point2<f32>,
+        radius: f32
+    },
+}
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io(PathBuf, io::Error),
+    Parse(toml::de::Error),
+    Serialize(toml::ser::Error),
+    /// A value parsed but cannot be used. `key` is the path, e.g. `spawn[0].count`.
+    Invalid { key: String, message: String },
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneError::Io(path, e) => write!(f, "cannot access {}: {}", path.display(), e),
+            SceneError::Parse(e) => write!(f, "{}", e),
+            SceneError::Serialize(e) => write!(f, "{}", e),
+            SceneError::Invalid { key, message } => write!(f, "invalid value for key `{}`: {}", key, message),
+        }
+    }
+}
+
+impl Error for SceneError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SceneError::Io(_, e) => Some(e),
+            SceneError::Parse(e) => Some(e),
+            SceneError::Serialize(e) => Some(e),
+            SceneError::Invalid { .. } => None,
+        }
+    }
+}
+
+impl Scene {
+    pub const DEFAULT_PATH: &'static str = "scene.toml";
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Scene, SceneError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|e| SceneError::Io(path.to_path_buf(), e))?;
+        Scene::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<Scene, SceneError> {
+        let scene: Scene = toml::from_str(text).map_err(SceneError::Parse)?;
+        scene.validate()?;
+        Ok(scene)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), SceneError> {
+        let path = path.as_ref();
+        fs::write(path, self.to_toml()?).map_err(|e| SceneError::Io(path.to_path_buf(), e))
+    }
+
+    pub fn to_toml(&self) -> Result<String, SceneError> {
+        toml::to_string(self).map_err(SceneError::Serialize)
+    }
+
+    /// The world size and obstacles of `simulation`, without spawn regions.
+    pub fn from_simulation(simulation: &Simulation) -> Scene {
+        Scene {
+            world: Some(WorldSize { width: simulation.width, height: simulation.height }),
+            obstacles: simulation.obstacles.iter()
+                .map(|obstacle| ObstacleScene {
+                    pos: obstacle.pos,
+                    radius: obstacle.radius,
+                    shape: obstacle.shape.clone(),
+                    motion: obstacle.motion.clone(),
+                })
+                .collect(),
+            spawn: Vec::new(),
+        }
+    }
+
+    /// Resizes the world, adds the obstacles and spawns the birds of the scene.
+    ///
+    /// Fails without changing anything if a spawn region names a species the
+    /// simulation does not have.
+    pub fn apply(&self, simulation: &mut Simulation) -> Result<(), SceneError> {
+        let mut species = Vec::new();
+        for (i, spawn) in self.spawn.iter().enumerate() {
+            species.push(match &spawn.species {
+                Some(name) => match simulation.species.iter().position(|species| &species.name == name) {
+                    Some(index) => Some(index),
+                    None => return Err(SceneError::Invalid { key: format!("spawn[{}].species", i), message: format!("unknown species `{}`", name) })
+                },
+                None => None
+            });
+        }
+
+        if let Some(world) = self.world {
+            simulation.width = world.width;
+            simulation.height = world.height;
+        }
+        for obstacle in self.obstacles.iter() {
+            simulation.obstacles.push(Obstacle {
+                motion: obstacle.motion.clone(),
+                ..Obstacle::with_shape(obstacle.pos, obstacle.radius, obstacle.shape.clone())
+            });
+        }
+        for (spawn, species) in self.spawn.iter().zip(species) {
+            simulation.spawn_region(spawn, species);
+        }
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), SceneError> {
+        if let Some(world) = self.world {
+            positive("world.width", world.width)?;
+            positive("world.height", world.height)?;
+        }
+        for (i, obstacle) in self.obstacles.iter().enumerate() {
+            let key = |name: &str| format!("obstacles[{}].{}", i, name);
+            finite(&key("pos"), &[obstacle.pos.x, obstacle.pos.y])?;
+            if let Some(radius) = obstacle.radius {
+                positive(&key("radius"), radius)?;
+            }
+            match &obstacle.shape {
+                Shape::Wall { start, end } => {
+                    finite(&key("shape"), &[start.x, start.y, end.x, end.y])?;
+                    if start == end {
+                        return Err(SceneError::Invalid { key: key("shape"), message: "a wall needs a start and end that differ".to_string() });
+                    }
+                },
+                Shape::Rect { half_width, half_height } => {
+                    positive(&key("shape.half_width"), *half_width)?;
+                    positive(&key("shape.half_height"), *half_height)?;
+                },
+                Shape::Polygon { vertices } if vertices.len() < 3 => {
+                    return Err(SceneError::Invalid { key: key("shape"), message: "a polygon needs at least 3 vertices".to_string() });
+                },
+                Shape::Polygon { vertices } if polygon_area(vertices).abs() < 1e-3 => {
+                    return Err(SceneError::Invalid { key: key("shape"), message: "a polygon needs vertices that are not all on one line".to_string() });
+                },
+                _ => ()
+            }
+            match &obstacle.motion {
+                Motion::Path { waypoints, .. } if waypoints.is_empty() => {
+                    return Err(SceneError::Invalid { key: key("motion"), message: "a path needs at least one waypoint".to_string() });
+                },
+                Motion::Path { speed, .. } | Motion::FollowMouse { speed } => non_negative(&key("motion.speed"), *speed)?,
+                Motion::Orbit { radius, .. } => positive(&key("motion.radius"), *radius)?,
+                _ => ()
+            }
+        }
+        for (i, spawn) in self.spawn.iter().enumerate() {
+            let key = |name: &str| format!("spawn[{}].{}", i, name);
+            if spawn.speed[0] < 0.0 || spawn.speed[0] > spawn.speed[1] {
+                return Err(SceneError::Invalid { key: key("speed"), message: "expected [min, max] with 0 <= min <= max".to_string() });
+            }
+            if spawn.heading[0] > spawn.heading[1] {
+                return Err(SceneError::Invalid { key: key("heading"), message: "expected [min, max] with min <= max".to_string() });
+            }
+            match spawn.region {
+                Region::Rect { min, max } => {
+                    finite(&key("region"), &[min.x, min.y, max.x, max.y])?;
+                    if min.x > max.x || min.y > max.y {
+                        return Err(SceneError::Invalid { key: key("region"), message: "`min` must be the top left corner".to_string() });
+                    }
+                },
+                Region::Circle { center, radius } => {
+                    finite(&key("region"), &[center.x, center.y])?;
+                    positive(&key("region.radius"), radius)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn positive(key: &str, value: f32) -> Result<(), SceneError> {
+    if value.is_finite() && value > 0.0 {
+        Ok(())
+    }
+    else {
+        Err(SceneError::Invalid { key: key.to_string(), message: format!("expected a positive number, got {}", value) })
+    }
+}
+
+fn non_negative(key: &str, value: f32) -> Result<(), SceneError> {
+    if value.is_finite() && value >= 0.0 {
+        Ok(())
+    }
+    else {
+        Err(SceneError::Invalid { key: key.to_string(), message: format!("expected a non-negative number, got {}", value) })
+    }
+}
+
+fn finite(key: &str, values: &[f32]) -> Result<(), SceneError> {
+    match values.iter().find(|value| !value.is_finite()) {
+        Some(value) => Err(SceneError::Invalid { key: key.to_string(), message: format!("expected finite coordinates, got {}", value) }),
+        None => Ok(())
+    }
+}
+
+/// Signed area enclosed by `vertices`, by the shoelace formula.
+fn polygon_area(vertices: &[Vector2<f32>]) -> f32 {
+    let mut twice_area = 0.0;
+    for i in 0..vertices.len() {
+        let (a, b) = (vertices[i], vertices[(i + 1) % vertices.len()]);
+        twice_area += a.x * b.y - b.x * a.y;
+    }
+    twice_area / 2.0
+}
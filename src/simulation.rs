@@ -0,0 +1,892 @@
+use ggez::nalgebra::{Point2, Vector2};
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64Mcg;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use std::cmp::Ordering;
+use std::f32::consts::PI;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::entities::{Bird, Motion, Obstacle, Predator, Shape};
+use crate::grid::SpatialGrid;
+use crate::metrics::FlockMetrics;
+use crate::rules::{ActiveRule, Rules, SteeringRule, World};
+use crate::scene::{Region, SpawnRegion};
+use crate::snapshot::{RuleState, Snapshot, SnapshotError};
+use crate::species::Species;
+use crate::tools::Tools;
+
+/// Tunable constants of the flocking model.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Parameters {
+    pub alignment_view_distance: f32,
+    pub separation_view_distance: f32,
+    pub cohesion_view_distance: f32,
+    /// Distance within which birds evade obstacles without a radius of their own.
+    pub obstacle_radius: f32,
+
+    /// Angles in degrees, centred on the heading, within which each rule sees.
+    pub alignment_field_of_view: f32,
+    pub separation_field_of_view: f32,
+    pub cohesion_field_of_view: f32,
+    pub obstacle_field_of_view: f32,
+    pub flee_field_of_view: f32,
+
+    pub max_speed: f32,
+    pub max_steering_velocity: f32,
+    pub random_movement: f32,
+
+    pub alignment_modifier: f32,
+    pub separation_modifier: f32,
+    pub cohesion_modifier: f32,
+    pub obstacle_modifier: f32,
+    pub obstacle_avoidance: ObstacleAvoidance,
+    pub look_ahead_distance: f32,
+
+    /// Which neighbours the flocking rules see. Applies to every species.
+    pub neighbourhood: Neighbourhood,
+    pub topological_neighbours: usize,
+
+    /// What happens at the edges of the world. Applies to every species.
+    pub boundary: Boundary,
+    pub boundary_margin: f32,
+    pub boundary_modifier: f32,
+
+    pub fear_radius: f32,
+    pub flee_modifier: f32,
+    pub predator_view_distance: f32,
+    pub predator_kill_radius: f32,
+    pub predator_max_speed: f32,
+    pub predator_max_steering_velocity: f32,
+    pub predator_target: PredatorTarget,
+}
+
+impl Parameters {
+    pub const ALIGNMENT_VIEW_DISTANCE: f32 = 100_f32;
+    pub const SEPARATION_VIEW_DISTANCE: f32 = 30_f32;
+    pub const COHESION_VIEW_DISTANCE: f32 = 100_f32;
+    pub const OBSTACLE_RADIUS: f32 = 50.0;
+
+    pub const FIELD_OF_VIEW: f32 = 360.0;
+
+    pub const MAX_SPEED: f32 = 3.5_f32;
+    pub const MAX_STEERING_VELOCITY: f32 = 0.16_f32;
+    pub const RANDOM_MOVEMENT: f32 = 0.1_f32;
+
+    pub const ALIGNMENT_MODIFIER: f32 = 1.6;
+    pub const SEPARATION_MODIFIER: f32 = 2.0;
+    pub const COHESION_MODIFIER: f32 = 1.0;
+    pub const OBSTACLE_MODIFIER: f32 = 2.5;
+    pub const LOOK_AHEAD_DISTANCE: f32 = 120.0;
+
+    pub const TOPOLOGICAL_NEIGHBOURS: usize = 7;
+
+    pub const BOUNDARY_MARGIN: f32 = 50.0;
+    pub const BOUNDARY_MODIFIER: f32 = 2.0;
+
+    pub const FEAR_RADIUS: f32 = 120.0;
+    pub const FLEE_MODIFIER: f32 = 4.0;
+    pub const PREDATOR_VIEW_DISTANCE: f32 = 300.0;
+    pub const PREDATOR_KILL_RADIUS: f32 = 8.0;
+    pub const PREDATOR_MAX_SPEED: f32 = 4.0;
+    pub const PREDATOR_MAX_STEERING_VELOCITY: f32 = 0.1;
+
+    /// The largest distance at which any rule perceives another bird.
+    pub fn max_view_distance(&self) -> f32 {
+        self.alignment_view_distance
+            .max(self.separation_view_distance)
+            .max(self.cohesion_view_distance)
+    }
+}
+
+impl Default for Parameters {
+    fn default() -> Self {
+        Parameters {
+            alignment_view_distance: Parameters::ALIGNMENT_VIEW_DISTANCE,
+            separation_view_distance: Parameters::SEPARATION_VIEW_DISTANCE,
+            cohesion_view_distance: Parameters::COHESION_VIEW_DISTANCE,
+            obstacle_radius: Parameters::OBSTACLE_RADIUS,
+            alignment_field_of_view: Parameters::FIELD_OF_VIEW,
+            separation_field_of_view: Parameters::FIELD_OF_VIEW,
+            cohesion_field_of_view: Parameters::FIELD_OF_VIEW,
+            obstacle_field_of_view: Parameters::FIELD_OF_VIEW,
+            flee_field_of_view: Parameters::FIELD_OF_VIEW,
+            max_speed: Parameters::MAX_SPEED,
+            max_steering_velocity: Parameters::MAX_STEERING_VELOCITY,
+            random_movement: Parameters::RANDOM_MOVEMENT,
+            alignment_modifier: Parameters::ALIGNMENT_MODIFIER,
+            separation_modifier: Parameters::SEPARATION_MODIFIER,
+            cohesion_modifier: Parameters::COHESION_MODIFIER,
+            obstacle_modifier: Parameters::OBSTACLE_MODIFIER,
+            obstacle_avoidance: ObstacleAvoidance::Radial,
+            look_ahead_distance: Parameters::LOOK_AHEAD_DISTANCE,
+            neighbourhood: Neighbourhood::Metric,
+            topological_neighbours: Parameters::TOPOLOGICAL_NEIGHBOURS,
+            boundary: Boundary::Wrap,
+            boundary_margin: Parameters::BOUNDARY_MARGIN,
+            boundary_modifier: Parameters::BOUNDARY_MODIFIER,
+            fear_radius: Parameters::FEAR_RADIUS,
+            flee_modifier: Parameters::FLEE_MODIFIER,
+            predator_view_distance: Parameters::PREDATOR_VIEW_DISTANCE,
+            predator_kill_radius: Parameters::PREDATOR_KILL_RADIUS,
+            predator_max_speed: Parameters::PREDATOR_MAX_SPEED,
+            predator_max_steering_velocity: Parameters::PREDATOR_MAX_STEERING_VELOCITY,
+            predator_target: PredatorTarget::Nearest,
+        }
+    }
+}
+
+/// How the obstacle evasion rule reacts to obstacles.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ObstacleAvoidance {
+    /// Push away from every obstacle closer than its `radius`.
+    Radial,
+    /// Cast feeler rays `look_ahead_distance` along the velocity and steer
+    /// around the first obstacle they hit.
+    LookAhead,
+}
+
+/// Which birds the alignment, separation and cohesion rules react to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Neighbourhood {
+    /// Every bird within the rule's view distance. The original behaviour.
+    Metric,
+    /// The `topological_neighbours` nearest birds, however far away they are.
+    Topological,
+}
+
+impl Neighbourhood {
+    pub const NAMES: &'static [&'static str] = &["metric", "topological"];
+
+    /// The mode after this one, for cycling through them at runtime.
+    pub fn next(self) -> Neighbourhood {
+        match self {
+            Neighbourhood::Metric => Neighbourhood::Topological,
+            Neighbourhood::Topological => Neighbourhood::Metric,
+        }
+    }
+}
+
+impl FromStr for Neighbourhood {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "metric" => Ok(Neighbourhood::Metric),
+            "topological" => Ok(Neighbourhood::Topological),
+            _ => Err(format!("unknown neighbourhood `{}`, expected one of {}", s, Neighbourhood::NAMES.join(", ")))
+        }
+    }
+}
+
+impl fmt::Display for Neighbourhood {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Neighbourhood::Metric => "metric",
+            Neighbourhood::Topological => "topological",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// How birds are kept inside the world.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Boundary {
+    /// Leaving one edge re-enters at the opposite one. The original behaviour.
+    Wrap,
+    /// Edges reflect birds like walls.
+    Bounce,
+    /// The containment rule turns birds back within `boundary_margin` of an
+    /// edge, and they are clamped to the world if they still reach it.
+    Contain,
+    /// Birds that leave the world die.
+    Kill,
+}
+
+impl Boundary {
+    pub const NAMES: &'static [&'static str] = &["wrap", "bounce", "contain", "kill"];
+
+    /// The mode after this one, for cycling through them at runtime.
+    pub fn next(self) -> Boundary {
+        match self {
+            Boundary::Wrap => Boundary::Bounce,
+            Boundary::Bounce => Boundary::Contain,
+            Boundary::Contain => Boundary::Kill,
+            Boundary::Kill => Boundary::Wrap,
+        }
+    }
+
+    /// Brings an entity that moved to `pos` back into the world, adjusting
+    /// `vel` as needed. Returns false if it should die instead.
+    pub fn apply(self, pos: &mut Point2<f32>, vel: &mut Vector2<f32>, width: f32, height: f32) -> bool {
+        match self {
+            Boundary::Wrap => Tools::wrap_position(pos, width, height),
+            Boundary::Bounce => Tools::bounce_position(pos, vel, width, height),
+            Boundary::Contain => Tools::clamp_position(pos, width, height),
+            Boundary::Kill => return pos.x >= 0.0 && pos.x <= width && pos.y >= 0.0 && pos.y <= height,
+        }
+        true
+    }
+}
+
+impl FromStr for Boundary {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "wrap" => Ok(Boundary::Wrap),
+            "bounce" => Ok(Boundary::Bounce),
+            "contain" => Ok(Boundary::Contain),
+            "kill" => Ok(Boundary::Kill),
+            _ => Err(format!("unknown boundary `{}`, expected one of {}", s, Boundary::NAMES.join(", ")))
+        }
+    }
+}
+
+impl fmt::Display for Boundary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Boundary::Wrap => "wrap",
+            Boundary::Bounce => "bounce",
+            Boundary::Contain => "contain",
+            Boundary::Kill => "kill",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// What a predator chases.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PredatorTarget {
+    /// The closest bird within `predator_view_distance`.
+    Nearest,
+    /// The centre of the bird within `predator_view_distance` that has the
+    /// most neighbours within `cohesion_view_distance`.
+    Densest,
+}
+
+/// How neighbour candidates are found for each bird.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NeighbourSearch {
+    /// Check every other bird. Kept as the reference implementation.
+    BruteForce,
+    /// Only check birds in nearby cells of a `SpatialGrid`.
+    Grid,
+}
+
+/// How birds read each other's state during a step.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateMode {
+    /// Birds are updated in place one after another, so later birds see the
+    /// already moved earlier ones. This is the original behaviour.
+    Sequential,
+    /// Steering for every bird is computed from the previous tick's state and
+    /// applied afterwards. Runs across all cores with the `parallel` feature.
+    DoubleBuffered,
+}
+
+/// Where the initial flock is placed by `Simulation::spawn_flock`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SpawnPattern {
+    /// Every bird at the centre of the world.
+    Center,
+    /// Uniformly distributed over the whole world.
+    Random,
+    /// Evenly spaced rows and columns covering the world.
+    Grid,
+    /// Evenly spaced on a circle around the centre.
+    Circle,
+}
+
+impl SpawnPattern {
+    pub const NAMES: &'static [&'static str] = &["center", "random", "grid", "circle"];
+}
+
+impl FromStr for SpawnPattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "center" => Ok(SpawnPattern::Center),
+            "random" => Ok(SpawnPattern::Random),
+            "grid" => Ok(SpawnPattern::Grid),
+            "circle" => Ok(SpawnPattern::Circle),
+            _ => Err(format!("unknown spawn pattern `{}`, expected one of {}", s, SpawnPattern::NAMES.join(", ")))
+        }
+    }
+}
+
+impl fmt::Display for SpawnPattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            SpawnPattern::Center => "center",
+            SpawnPattern::Random => "random",
+            SpawnPattern::Grid => "grid",
+            SpawnPattern::Circle => "circle",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The flock model without any rendering or input handling.
+///
+/// All randomness comes from a generator seeded with `seed`, so the same seed,
+/// parameters and sequence of calls always produce identical trajectories.
+pub struct Simulation {
+    pub birds: Vec<Bird>,
+    pub obstacles: Vec<Obstacle>,
+    pub predators: Vec<Predator>,
+    pub rules: Vec<ActiveRule>,
+    /// Species of the flock, indexed by `Bird::species`. Never empty.
+    pub species: Vec<Species>,
+    /// Constants for predators and for every species without its own.
+    pub parameters: Parameters,
+    pub neighbour_search: NeighbourSearch,
+    pub update_mode: UpdateMode,
+    pub width: f32,
+    pub height: f32,
+    /// Mouse cursor in world coordinates, followed by `Motion::FollowMouse` obstacles.
+    pub mouse: Option<Point2<f32>>,
+    pub tick: u64,
+    pub time: f32,
+    pub seed: u64,
+    /// Whether double-buffered steps run across all cores. Turning it off
+    /// gives the same result, only slower.
+    #[cfg(feature = "parallel")]
+    pub parallel: bool,
+    rng: Pcg64Mcg,
+    grid: SpatialGrid,
+    neighbours: Vec<usize>,
+    next_bird_id: u64,
+}
+
+impl Simulation {
+    /// Creates an empty simulation with a randomly chosen seed.
+    pub fn new(width: f32, height: f32) -> Self {
+        Simulation::with_seed(width, height, rand::random())
+    }
+
+    pub fn with_seed(width: f32, height: f32, seed: u64) -> Self {
+        Simulation {
+            birds: Vec::new(),
+            obstacles: Vec::new(),
+            predators: Vec::new(),
+            rules: Rules::default().build(),
+            species: vec![Species::default()],
+            parameters: Parameters::default(),
+            neighbour_search: NeighbourSearch::Grid,
+            update_mode: UpdateMode::DoubleBuffered,
+            width,
+            height,
+            mouse: None,
+            tick: 0,
+            time: 0.0,
+            seed,
+            #[cfg(feature = "parallel")]
+            parallel: true,
+            rng: Pcg64Mcg::seed_from_u64(seed),
+            grid: SpatialGrid::new(width, height, Parameters::default().max_view_distance()),
+            neighbours: Vec::new(),
+            next_bird_id: 0,
+        }
+    }
+
+    /// Captures the whole state of the simulation, see `Snapshot`.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            width: self.width,
+            height: self.height,
+            tick: self.tick,
+            time: self.time,
+            seed: self.seed,
+            parameters: self.parameters,
+            species: self.species.clone(),
+            rules: self.rules.iter()
+                .map(|active| RuleState { name: active.rule.name().to_string(), enabled: active.enabled })
+                .collect(),
+            neighbour_search: self.neighbour_search,
+            update_mode: self.update_mode,
+            birds: self.birds.clone(),
+            obstacles: self.obstacles.clone(),
+            predators: self.predators.clone(),
+            next_bird_id: self.next_bird_id,
+            rng: self.rng.clone(),
+        }
+    }
+
+    /// Recreates the simulation `snapshot` was taken of, ready to continue
+    /// from the same tick. Fails if it uses a rule that is not built in or
+    /// does not pass `Snapshot::validate`.
+    pub fn from_snapshot(snapshot: Snapshot) -> Result<Simulation, SnapshotError> {
+        snapshot.validate()?;
+        let mut rules = Vec::new();
+        for state in snapshot.rules {
+            match Rules::builtin(&state.name) {
+                Some(rule) => rules.push(ActiveRule { rule, enabled: state.enabled }),
+                None => return Err(SnapshotError::UnknownRule(state.name))
+            }
+        }
+
+        let mut simulation = Simulation::with_seed(snapshot.width, snapshot.height, snapshot.seed);
+        simulation.tick = snapshot.tick;
+        simulation.time = snapshot.time;
+        simulation.parameters = snapshot.parameters;
+        simulation.set_species(snapshot.species);
+        simulation.rules = rules;
+        simulation.neighbour_search = snapshot.neighbour_search;
+        simulation.update_mode = snapshot.update_mode;
+        simulation.birds = snapshot.birds;
+        simulation.obstacles = snapshot.obstacles;
+        simulation.predators = snapshot.predators;
+        simulation.next_bird_id = snapshot.next_bird_id;
+        simulation.rng = snapshot.rng;
+        Ok(simulation)
+    }
+
+    /// Appends `rule` to the end of the rule list, enabled.
+    pub fn add_rule<R: SteeringRule + 'static>(&mut self, rule: R) {
+        self.rules.push(ActiveRule { rule: Box::new(rule), enabled: true });
+    }
+
+    /// Replaces the rule list with the built-in rules described by `rules`.
+    pub fn set_rules(&mut self, rules: &Rules) {
+        self.rules = rules.build();
+    }
+
+    pub fn rule(&self, name: &str) -> Option<&ActiveRule> {
+        self.rules.iter().find(|active| active.rule.name() == name)
+    }
+
+    pub fn is_rule_enabled(&self, name: &str) -> bool {
+        self.rule(name).is_some_and(|active| active.enabled)
+    }
+
+    /// Enables or disables the rule called `name`. Returns false if there is no such rule.
+    pub fn set_rule_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        match self.rules.iter_mut().find(|active| active.rule.name() == name) {
+            Some(active) => {
+                active.enabled = enabled;
+                true
+            },
+            None => false
+        }
+    }
+
+    /// Flips the rule called `name` and returns its new state.
+    pub fn toggle_rule(&mut self, name: &str) -> Option<bool> {
+        let enabled = !self.rule(name)?.enabled;
+        self.set_rule_enabled(name, enabled);
+        Some(enabled)
+    }
+
+    /// Replaces the species list. An empty list leaves a single default species.
+    ///
+    /// Birds keep their species index, so it should not shrink below the
+    /// species of any existing bird.
+    pub fn set_species(&mut self, species: Vec<Species>) {
+        self.species = if species.is_empty() { vec![Species::default()] } else { species };
+    }
+
+    /// The constants that apply to `bird`, see `World::parameters_of`.
+    pub fn parameters_of(&self, bird: &Bird) -> &Parameters {
+        self.species[bird.species].parameters_or(&self.parameters)
+    }
+
+    /// The largest distance at which a bird of any species perceives another bird.
+    pub fn max_view_distance(&self) -> f32 {
+        self.species.iter()
+            .map(|species| species.parameters_or(&self.parameters).max_view_distance())
+            .fold(0.0, f32::max)
+    }
+
+    /// Adds a bird of the first species at `pos` with a small random initial velocity.
+    pub fn spawn_bird(&mut self, pos: Point2<f32>) {
+        self.spawn_bird_of_species(pos, 0);
+    }
+
+    /// Adds a bird of `species` at `pos` with a small random initial velocity.
+    pub fn spawn_bird_of_species(&mut self, pos: Point2<f32>, species: usize) {
+        let vel = Vector2::new(self.rng.gen_range(-0.1 .. 0.1), self.rng.gen_range(-0.1 .. 0.1));
+        let mut bird = Bird::new(pos, vel);
+        bird.species = species;
+        self.add_bird(bird);
+    }
+
+    /// Appends `bird` with a new id, starting at 1, and returns the id.
+    pub fn add_bird(&mut self, mut bird: Bird) -> u64 {
+        self.next_bird_id += 1;
+        bird.id = self.next_bird_id;
+        self.birds.push(bird);
+        self.next_bird_id
+    }
+
+    /// Adds `count` birds laid out according to `pattern`, taking turns between the species.
+    pub fn spawn_flock(&mut self, count: usize, pattern: SpawnPattern) {
+        let center = Point2::new(self.width / 2.0, self.height / 2.0);
+        let columns = (count as f32).sqrt().ceil().max(1.0) as usize;
+        let rows = (count + columns - 1) / columns.max(1);
+        let radius = self.width.min(self.height) / 3.0;
+
+        for i in 0..count {
+            let pos = match pattern {
+                SpawnPattern::Center => center,
+                SpawnPattern::Random => Point2::new(self.rng.gen_range(0.0 .. self.width), self.rng.gen_range(0.0 .. self.height)),
+                SpawnPattern::Grid => Point2::new(
+                    (i % columns) as f32 * self.width / columns as f32 + self.width / columns as f32 / 2.0,
+                    (i / columns) as f32 * self.height / rows as f32 + self.height / rows as f32 / 2.0),
+                SpawnPattern::Circle => {
+                    let angle = i as f32 / count as f32 * 2.0 * PI;
+                    Point2::new(center.x + radius * angle.cos(), center.y + radius * angle.sin())
+                }
+            };
+            self.spawn_bird_of_species(pos, i % self.species.len());
+        }
+    }
+
+    /// Adds the birds of a scene's spawn region, all of `species` or taking
+    /// turns between the species when it is `None`.
+    pub fn spawn_region(&mut self, spawn: &SpawnRegion, species: Option<usize>) {
+        // Sampled as `min + (max - min) * u` rather than with `gen_range`,
+        // which panics on the empty ranges a scene may ask for.
+        let mut between = |[min, max]: [f32; 2]| min + (max - min) * self.rng.gen::<f32>();
+        let mut birds = Vec::with_capacity(spawn.count);
+        for _ in 0..spawn.count {
+            let pos = match spawn.region {
+                Region::Rect { min, max } => Point2::new(between([min.x, max.x]), between([min.y, max.y])),
+                Region::Circle { center, radius } => {
+                    // The square root spreads the birds evenly over the area.
+                    let distance = radius * between([0.0, 1.0]).sqrt();
+                    let angle = between([0.0, 2.0 * PI]);
+                    Point2::new(center.x + distance * angle.cos(), center.y + distance * angle.sin())
+                }
+            };
+            let speed = between(spawn.speed);
+            let heading = between(spawn.heading).to_radians();
+            birds.push((pos, Vector2::new(speed * heading.cos(), speed * heading.sin())));
+        }
+        for (i, (pos, vel)) in birds.into_iter().enumerate() {
+            let mut bird = Bird::new(pos, vel);
+            bird.species = species.unwrap_or(i % self.species.len());
+            self.add_bird(bird);
+        }
+    }
+
+    pub fn spawn_obstacle(&mut self, pos: Point2<f32>) {
+        self.spawn_moving_obstacle(pos, Motion::Static);
+    }
+
+    pub fn spawn_moving_obstacle(&mut self, pos: Point2<f32>, motion: Motion) {
+        self.obstacles.push(Obstacle::with_motion(pos, None, motion));
+    }
+
+    /// Adds an obstacle at `pos` drifting at `speed` in a random direction.
+    pub fn spawn_drifting_obstacle(&mut self, pos: Point2<f32>, speed: f32) {
+        let angle = self.rng.gen_range(0.0 .. 2.0 * PI);
+        self.spawn_moving_obstacle(pos, Motion::Drift { vel: Vector2::new(angle.cos(), angle.sin()) * speed });
+    }
+
+    pub fn spawn_shaped_obstacle(&mut self, pos: Point2<f32>, shape: Shape) {
+        self.obstacles.push(Obstacle::with_shape(pos, None, shape));
+    }
+
+    /// Adds a predator at `pos` with a small random initial velocity.
+    pub fn spawn_predator(&mut self, pos: Point2<f32>) {
+        let vel = Vector2::new(self.rng.gen_range(-0.1 .. 0.1), self.rng.gen_range(-0.1 .. 0.1));
+        self.predators.push(Predator::new(pos, vel));
+    }
+
+    /// Marks every bird as dead; they are removed on the next step.
+    pub fn kill_birds(&mut self) {
+        for bird in self.birds.iter_mut() {
+            bird.is_alive = false;
+        }
+    }
+
+    /// Marks every obstacle as dead; they are removed on the next step.
+    pub fn kill_obstacles(&mut self) {
+        for obstacle in self.obstacles.iter_mut() {
+            obstacle.is_alive = false;
+        }
+    }
+
+    /// Polarization, milling and the other order parameters of the flock as it is now.
+    pub fn metrics(&self) -> FlockMetrics {
+        FlockMetrics::measure(self)
+    }
+
+    /// Marks every predator as dead; they are removed on the next step.
+    pub fn kill_predators(&mut self) {
+        for predator in self.predators.iter_mut() {
+            predator.is_alive = false;
+        }
+    }
+
+    /// Advances the flock by one tick lasting `dt` seconds.
+    ///
+    /// The steering constants are tuned per tick, so `dt` only advances `time`.
+    pub fn step(&mut self, dt: f32) {
+        if self.neighbour_search == NeighbourSearch::Grid {
+            self.grid.resize(self.width, self.height, self.max_view_distance());
+            self.grid.rebuild(self.birds.iter().map(|bird| bird.pos));
+        }
+
+        for obstacle in self.obstacles.iter_mut() {
+            obstacle.update(self.width, self.height, self.mouse);
+        }
+        self.step_predators();
+        match self.update_mode {
+            UpdateMode::Sequential => self.step_sequential(),
+            UpdateMode::DoubleBuffered => self.step_double_buffered()
+        }
+        // remove entities that are not alive
+        self.birds.retain(|bird| bird.is_alive);
+        self.obstacles.retain(|obstacle| obstacle.is_alive);
+        self.predators.retain(|predator| predator.is_alive);
+
+        self.tick += 1;
+        self.time += dt;
+    }
+
+    fn step_sequential(&mut self) {
+        let mut neighbours = std::mem::take(&mut self.neighbours);
+        for i in 0..self.birds.len() {
+            if !self.birds[i].is_alive {
+                continue;
+            }
+            let mut rng = Pcg64Mcg::seed_from_u64(self.rng.gen());
+            let world = World {
+                birds: &self.birds,
+                obstacles: &self.obstacles,
+                predators: &self.predators,
+                species: &self.species,
+                parameters: &self.parameters,
+                width: self.width,
+                height: self.height,
+            };
+            self.find_neighbours(i, &mut neighbours);
+            let steering = steering(&self.rules, i, &neighbours, &world, &mut rng);
+
+            let old_pos = self.birds[i].pos;
+            let max_speed = self.parameters_of(&self.birds[i]).max_speed;
+            self.birds[i].update(steering, max_speed, self.parameters.boundary, self.width, self.height);
+
+            if self.neighbour_search == NeighbourSearch::Grid {
+                // later birds in this tick must see where this one moved to
+                self.grid.relocate(i, old_pos, self.birds[i].pos);
+            }
+        }
+        self.neighbours = neighbours;
+    }
+
+    fn step_double_buffered(&mut self) {
+        // seeded up front, in bird order, so the result does not depend on threading
+        let seeds: Vec<u64> = (0..self.birds.len()).map(|_| self.rng.gen()).collect();
+
+        let world = World {
+            birds: &self.birds,
+            obstacles: &self.obstacles,
+            predators: &self.predators,
+            species: &self.species,
+            parameters: &self.parameters,
+            width: self.width,
+            height: self.height,
+        };
+        let this = &*self;
+        let steer = |i: usize, seed: u64, neighbours: &mut Vec<usize>| {
+            if !this.birds[i].is_alive {
+                return Vec::new();
+            }
+            this.find_neighbours(i, neighbours);
+            steering(&this.rules, i, neighbours, &world, &mut Pcg64Mcg::seed_from_u64(seed))
+        };
+        let serial = |seeds: Vec<u64>| -> Vec<Vec<Vector2<f32>>> {
+            let mut neighbours = Vec::new();
+            seeds
+                .into_iter()
+                .enumerate()
+                .map(|(i, seed)| steer(i, seed, &mut neighbours))
+                .collect()
+        };
+        #[cfg(feature = "parallel")]
+        let steerings: Vec<Vec<Vector2<f32>>> = if this.parallel {
+            seeds
+                .into_par_iter()
+                .enumerate()
+                .map_init(Vec::new, |neighbours, (i, seed)| steer(i, seed, neighbours))
+                .collect()
+        } else {
+            serial(seeds)
+        };
+        #[cfg(not(feature = "parallel"))]
+        let steerings = serial(seeds);
+
+        for (bird, steering) in self.birds.iter_mut().zip(steerings).filter(|(bird, _)| bird.is_alive) {
+            let max_speed = self.species[bird.species].parameters_or(&self.parameters).max_speed;
+            bird.update(steering, max_speed, self.parameters.boundary, self.width, self.height);
+        }
+    }
+
+    /// Moves every predator towards its target and kills the birds it reaches.
+    ///
+    /// Runs before the birds move, so they flee from where the predators are now.
+    fn step_predators(&mut self) {
+        let mut candidates = std::mem::take(&mut self.neighbours);
+        for p in 0..self.predators.len() {
+            let mut steer: Vector2<f32> = Vector2::new(0.0, 0.0);
+            if let Some(target) = self.predator_target(self.predators[p].pos, &mut candidates) {
+                let mut desired: Vector2<f32> = self.offset(self.predators[p].pos, target);
+                if Tools::vector_length(&desired) > 0.0 {
+                    Tools::normalize_vector(&mut desired);
+                    desired *= self.parameters.predator_max_speed;
+                    steer = desired - self.predators[p].vel;
+                    Tools::limit_vector(&mut steer, self.parameters.predator_max_steering_velocity);
+                }
+            }
+            self.predators[p].update(steer, self.parameters.predator_max_speed, self.parameters.boundary, self.width, self.height);
+
+            let pos = self.predators[p].pos;
+            self.birds_near(pos, self.parameters.predator_kill_radius, &mut candidates);
+            for &j in candidates.iter() {
+                if self.distance(self.birds[j].pos, pos) <= self.parameters.predator_kill_radius {
+                    self.birds[j].is_alive = false;
+                }
+            }
+        }
+        self.neighbours = candidates;
+    }
+
+    fn predator_target(&self, pos: Point2<f32>, candidates: &mut Vec<usize>) -> Option<Point2<f32>> {
+        let view_distance = self.parameters.predator_view_distance;
+        self.birds_near(pos, view_distance, candidates);
+        let visible = candidates.iter().cloned().filter(|&j| {
+            self.birds[j].is_alive && self.distance(self.birds[j].pos, pos) <= view_distance
+        });
+
+        match self.parameters.predator_target {
+            PredatorTarget::Nearest => visible
+                .map(|j| (self.distance(self.birds[j].pos, pos), j))
+                .fold(None, |nearest: Option<(f32, usize)>, (d, j)| match nearest {
+                    Some((nearest_distance, _)) if nearest_distance <= d => nearest,
+                    _ => Some((d, j))
+                })
+                .map(|(_, j)| self.birds[j].pos),
+            PredatorTarget::Densest => {
+                let radius = self.parameters.cohesion_view_distance;
+                let mut group = Vec::new();
+                let mut densest: Option<(usize, Point2<f32>)> = None;
+                for j in visible.collect::<Vec<usize>>() {
+                    self.birds_near(self.birds[j].pos, radius, &mut group);
+                    // relative to bird `j`, so groups across a wrapping edge stay together
+                    let mut offset_sum: Vector2<f32> = Vector2::new(0.0, 0.0);
+                    let mut count = 0;
+                    for &k in group.iter() {
+                        let offset = self.offset(self.birds[j].pos, self.birds[k].pos);
+                        if self.birds[k].is_alive && Tools::vector_length(&offset) <= radius {
+                            offset_sum += offset;
+                            count += 1;
+                        }
+                    }
+                    if densest.is_none_or(|(densest_count, _)| count > densest_count) {
+                        densest = Some((count, self.birds[j].pos + offset_sum / count as f32));
+                    }
+                }
+                densest.map(|(_, center)| center)
+            }
+        }
+    }
+
+    /// See `World::offset`.
+    pub fn offset(&self, from: Point2<f32>, to: Point2<f32>) -> Vector2<f32> {
+        if self.parameters.boundary == Boundary::Wrap {
+            Tools::wrapped_offset(from, to, self.width, self.height)
+        }
+        else {
+            to - from
+        }
+    }
+
+    /// Length of `offset(a, b)`.
+    pub fn distance(&self, a: Point2<f32>, b: Point2<f32>) -> f32 {
+        Tools::vector_length(&self.offset(a, b))
+    }
+
+    /// Fills `neighbours` with the candidates `SteeringRule::steer` expects for bird `i`.
+    fn find_neighbours(&self, i: usize, neighbours: &mut Vec<usize>) {
+        match self.parameters.neighbourhood {
+            Neighbourhood::Metric => self.birds_near(self.birds[i].pos, self.max_view_distance(), neighbours),
+            Neighbourhood::Topological => self.nearest_birds(i, self.parameters.topological_neighbours, neighbours)
+        }
+        // birds caught by a predator this tick are gone already
+        neighbours.retain(|&j| self.birds[j].is_alive);
+    }
+
+    /// Fills `result` with the `k` live birds closest to bird `i`, or every
+    /// other live bird if there are fewer, sorted ascending.
+    ///
+    /// Searches a growing radius, starting at the grid's cell size, until it
+    /// holds at least `k` birds, so no closer bird can be missed. Ties are
+    /// broken by index.
+    fn nearest_birds(&self, i: usize, k: usize, result: &mut Vec<usize>) {
+        let pos = self.birds[i].pos;
+        let mut radius = self.max_view_distance();
+        loop {
+            self.birds_near(pos, radius, result);
+            let found_all = result.len() >= self.birds.len();
+            result.retain(|&j| j != i && self.birds[j].is_alive);
+            let within = result.iter().filter(|&&j| self.distance(pos, self.birds[j].pos) <= radius).count();
+            if within >= k {
+                result.retain(|&j| self.distance(pos, self.birds[j].pos) <= radius);
+                break;
+            }
+            if found_all {
+                break;
+            }
+            radius *= 2.0;
+        }
+        result.sort_unstable_by(|&a, &b| {
+            (self.distance(pos, self.birds[a].pos), a)
+                .partial_cmp(&(self.distance(pos, self.birds[b].pos), b))
+                .unwrap_or(Ordering::Equal)
+        });
+        result.truncate(k);
+        result.sort_unstable();
+    }
+
+    /// Fills `result` with the indices of birds that may lie within `radius` of `pos`.
+    fn birds_near(&self, pos: Point2<f32>, radius: f32, result: &mut Vec<usize>) {
+        match self.neighbour_search {
+            NeighbourSearch::BruteForce => {
+                result.clear();
+                result.extend(0..self.birds.len());
+            },
+            NeighbourSearch::Grid if self.parameters.boundary == Boundary::Wrap => {
+                self.grid.query_wrapped(pos, radius, self.width, self.height, result)
+            },
+            NeighbourSearch::Grid => self.grid.query(pos, radius, result)
+        }
+    }
+}
+
+/// Evaluates every rule for bird `i`, with zero for the disabled ones.
+fn steering(rules: &[ActiveRule], i: usize, neighbours: &[usize], world: &World, rng: &mut Pcg64Mcg) -> Vec<Vector2<f32>> {
+    rules.iter()
+        .map(|active| {
+            if active.enabled {
+                active.rule.steer(&world.birds[i], neighbours, world, rng)
+            }
+            else {
+                Vector2::new(0.0, 0.0)
+            }
+        })
+        .collect()
+}
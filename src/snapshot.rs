@@ -0,0 +1,215 @@
+use rand_pcg::Pcg64Mcg;
+use serde::{Deserialize, Serialize};
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::config::{self, ConfigError};
+use crate::entities::{Bird, Obstacle, Predator};
+use crate::simulation::{NeighbourSearch, Parameters, UpdateMode};
+use crate::species::Species;
+
+/// The complete state of a `Simulation`, made with `Simulation::snapshot` and
+/// turned back into one with `Simulation::from_snapshot`.
+///
+/// Includes the random number generator, so a restored simulation continues
+/// exactly as the original would have. Saved as JSON.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub width: f32,
+    pub height: f32,
+    pub tick: u64,
+    pub time: f32,
+    pub seed: u64,
+    pub parameters: Parameters,
+    pub species: Vec<Species>,
+    /// The steering rules in order. Only built-in rules can be restored.
+    pub rules: Vec<RuleState>,
+    pub neighbour_search: NeighbourSearch,
+    pub update_mode: UpdateMode,
+    pub birds: Vec<Bird>,
+    pub obstacles: Vec<Obstacle>,
+    pub predators: Vec<Predator>,
+    pub(crate) next_bird_id: u64,
+    pub(crate) rng: Pcg64Mcg,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleState {
+    pub name: String,
+    pub enabled: bool,
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(PathBuf, io::Error),
+    Json(serde_json::Error),
+    /// The snapshot names a rule that is not built in.
+    UnknownRule(String),
+    /// A value is out of range. `key` is the path to it, e.g. `birds[3].species`.
+    Invalid { key: String, message: String },
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotError::Io(path, e) => write!(f, "cannot access {}: {}", path.display(), e),
+            SnapshotError::Json(e) => write!(f, "{}", e),
+            SnapshotError::UnknownRule(name) => write!(f, "unknown rule `{}`", name),
+            SnapshotError::Invalid { key, message } => write!(f, "invalid value for `{}`: {}", key, message),
+        }
+    }
+}
+
+impl Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SnapshotError::Io(_, e) => Some(e),
+            SnapshotError::Json(e) => Some(e),
+            SnapshotError::UnknownRule(_) | SnapshotError::Invalid { .. } => None,
+        }
+    }
+}
+
+impl Snapshot {
+    pub const DEFAULT_PATH: &'static str = "snapshot.json";
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Snapshot, SnapshotError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|e| SnapshotError::Io(path.to_path_buf(), e))?;
+        Snapshot::from_json(&text)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), SnapshotError> {
+        let path = path.as_ref();
+        fs::write(path, self.to_json()?).map_err(|e| SnapshotError::Io(path.to_path_buf(), e))
+    }
+
+    pub fn from_json(text: &str) -> Result<Snapshot, SnapshotError> {
+        serde_json::from_str(text).map_err(SnapshotError::Json)
+    }
+
+    pub fn to_json(&self) -> Result<String, SnapshotError> {
+        serde_json::to_string(self).map_err(SnapshotError::Json)
+    }
+
+    /// Checks what a simulation restored from the snapshot relies on: a
+    /// positive world size, parameters that pass the config file checks,
+    /// species indices of existing species, finite positions and positive
+    /// obstacle radii.
+    pub fn validate(&self) -> Result<(), SnapshotError> {
+        let invalid = |key: String, message: String| Err(SnapshotError::Invalid { key, message });
+        for &(key, value) in [("width", self.width), ("height", self.height)].iter() {
+            if !(value.is_finite() && value > 0.0) {
+                return invalid(key.to_string(), format!("expected a positive number, got {}", value));
+            }
+        }
+        parameters("parameters", &self.parameters)?;
+        for (i, species) in self.species.iter().enumerate() {
+            if let Some(species_parameters) = &species.parameters {
+                parameters(&format!("species[{}].parameters", i), species_parameters)?;
+            }
+        }
+
+        // an empty list is restored as the default species
+        let species_count = self.species.len().max(1);
+        for (i, bird) in self.birds.iter().enumerate() {
+            if bird.species >= species_count {
+                return invalid(format!("birds[{}].species", i), format!("expected a species index below {}, got {}", species_count, bird.species));
+            }
+            finite(&format!("birds[{}]", i), &[bird.pos.x, bird.pos.y, bird.vel.x, bird.vel.y])?;
+        }
+        for (i, obstacle) in self.obstacles.iter().enumerate() {
+            finite(&format!("obstacles[{}]", i), &[obstacle.pos.x, obstacle.pos.y, obstacle.vel.x, obstacle.vel.y])?;
+            if let Some(radius) = obstacle.radius {
+                if !(radius.is_finite() && radius > 0.0) {
+                    return invalid(format!("obstacles[{}].radius", i), format!("expected a positive number, got {}", radius));
+                }
+            }
+        }
+        for (i, predator) in self.predators.iter().enumerate() {
+            finite(&format!("predators[{}]", i), &[predator.pos.x, predator.pos.y, predator.vel.x, predator.vel.y])?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs the config file checks on `value`, naming keys `<section>.<key>`.
+fn parameters(section: &str, value: &Parameters) -> Result<(), SnapshotError> {
+    config::validate_parameters(section, value).map_err(|e| match e {
+        ConfigError::Invalid { key, message } => SnapshotError::Invalid { key, message },
+        other => SnapshotError::Invalid { key: section.to_string(), message: other.to_string() }
+    })
+}
+
+fn finite(key: &str, values: &[f32]) -> Result<(), SnapshotError> {
+    match values.iter().find(|value| !value.is_finite()) {
+        Some(value) => Err(SnapshotError::Invalid { key: key.to_string(), message: format!("expected finite numbers, got {}", value) }),
+        None => Ok(())
+    }
+}
+
+/// Serializes a `Point2<f32>` as `[x, y]`, for `#[serde(with = "point")]`.
+pub mod point {
+    use ggez::nalgebra::Point2;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(point: &Point2<f32>, serializer: S) -> Result<S::Ok, S::Error> {
+        [point.x, point.y].serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Point2<f32>, D::Error> {
+        let [x, y] = <[f32; 2]>::deserialize(deserializer)?;
+        Ok(Point2::new(x, y))
+    }
+}
+
+/// Serializes a `Vector2<f32>` as `[x, y]`, for `#[serde(with = "vector")]`.
+pub mod vector {
+    use ggez::nalgebra::Vector2;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(vector: &Vector2<f32>, serializer: S) -> Result<S::Ok, S::Error> {
+        [vector.x, vector.y].serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vector2<f32>, D::Error> {
+        let [x, y] = <[f32; 2]>::deserialize(deserializer)?;
+        Ok(Vector2::new(x, y))
+    }
+}
+
+/// Like `point`, for a list of points.
+pub mod points {
+    use ggez::nalgebra::Point2;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(points: &[Point2<f32>], serializer: S) -> Result<S::Ok, S::Error> {
+        let pairs: Vec<[f32; 2]> = points.iter().map(|point| [point.x, point.y]).collect();
+        pairs.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Point2<f32>>, D::Error> {
+        let pairs = Vec::<[f32; 2]>::deserialize(deserializer)?;
+        Ok(pairs.into_iter().map(|[x, y]| Point2::new(x, y)).collect())
+    }
+}
+
+/// Like `vector`, for a list of vectors.
+pub mod vectors {
+    use ggez::nalgebra::Vector2;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(vectors: &[Vector2<f32>], serializer: S) -> Result<S::Ok, S::Error> {
+        let pairs: Vec<[f32; 2]> = vectors.iter().map(|vector| [vector.x, vector.y]).collect();
+        pairs.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Vector2<f32>>, D::Error> {
+        let pairs = Vec::<[f32; 2]>::deserialize(deserializer)?;
+        Ok(pairs.into_iter().map(|[x, y]| Vector2::new(x, y)).collect())
+    }
+}
@@ -0,0 +1,172 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+
+use crate::simulation::Parameters;
+
+/// How birds of one species react to neighbours of another.
+///
+/// Each level includes the ones before it, so `Align` also separates and
+/// `Cohere` treats the other species like the bird's own.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Interaction {
+    /// The other bird is not perceived at all.
+    Ignore,
+    /// Only separation steers away from it.
+    Avoid,
+    /// Separation and alignment apply, but not cohesion.
+    Align,
+    /// Every flocking rule applies.
+    Cohere,
+}
+
+/// A kind of bird with its own constants, look and reactions to other kinds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Species {
+    pub name: String,
+    /// Constants for birds of this species, or `None` to use the simulation's.
+    pub parameters: Option<Parameters>,
+    /// Tint of the bird sprite.
+    pub color: (u8, u8, u8),
+    /// Sprite path inside the resources directory, or `None` for the bird sprite.
+    pub sprite: Option<String>,
+    /// Reaction to each species, indexed like `Simulation::species`.
+    /// Missing entries are `Interaction::Cohere`.
+    pub interactions: Vec<Interaction>,
+}
+
+impl Species {
+    pub fn new(name: &str) -> Self {
+        Species {
+            name: name.to_string(),
+            parameters: None,
+            color: (255, 255, 255),
+            sprite: None,
+            interactions: Vec::new(),
+        }
+    }
+
+    /// The constants for birds of this species, falling back to `default`.
+    pub fn parameters_or<'a>(&'a self, default: &'a Parameters) -> &'a Parameters {
+        self.parameters.as_ref().unwrap_or(default)
+    }
+
+    /// How birds of this species react to birds of species `other`.
+    pub fn interaction(&self, other: usize) -> Interaction {
+        self.interactions.get(other).cloned().unwrap_or(Interaction::Cohere)
+    }
+}
+
+impl Default for Species {
+    fn default() -> Self {
+        Species::new("default")
+    }
+}
+
+/// A `[[species]]` entry of the config file.
+///
+/// ```toml
+/// [[species]]
+/// name = "sparrow"
+/// color = [255, 180, 120]
+/// sprite = "/bird.png"
+///
+/// [species.parameters]
+/// max_speed = 4.5
+///
+/// [species.interactions]
+/// starling = "avoid"
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SpeciesConfig {
+    pub name: String,
+    #[serde(default = "SpeciesConfig::default_color")]
+    pub color: (u8, u8, u8),
+    #[serde(default)]
+    pub sprite: Option<String>,
+    /// Overrides of the `[parameters]` section for this species.
+    #[serde(default)]
+    pub parameters: ParameterOverrides,
+    /// Reaction to other species by name. Unlisted species, including this one, are `cohere`.
+    #[serde(default)]
+    pub interactions: BTreeMap<String, Interaction>,
+}
+
+impl SpeciesConfig {
+    fn default_color() -> (u8, u8, u8) {
+        (255, 255, 255)
+    }
+
+    /// Builds the species, resolving names in `interactions` against `names`
+    /// and missing parameters against `base`.
+    pub fn build(&self, base: &Parameters, names: &[&str]) -> Species {
+        Species {
+            name: self.name.clone(),
+            parameters: Some(self.parameters.apply(base)),
+            color: self.color,
+            sprite: self.sprite.clone(),
+            interactions: names.iter()
+                .map(|name| self.interactions.get(*name).cloned().unwrap_or(Interaction::Cohere))
+                .collect(),
+        }
+    }
+}
+
+/// The per-bird keys of `Parameters` that a species may change.
+#[derive(Debug, Copy, Clone, PartialEq, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ParameterOverrides {
+    pub alignment_view_distance: Option<f32>,
+    pub separation_view_distance: Option<f32>,
+    pub cohesion_view_distance: Option<f32>,
+    pub obstacle_radius: Option<f32>,
+
+    pub alignment_field_of_view: Option<f32>,
+    pub separation_field_of_view: Option<f32>,
+    pub cohesion_field_of_view: Option<f32>,
+    pub obstacle_field_of_view: Option<f32>,
+    pub flee_field_of_view: Option<f32>,
+
+    pub max_speed: Option<f32>,
+    pub max_steering_velocity: Option<f32>,
+    pub random_movement: Option<f32>,
+
+    pub alignment_modifier: Option<f32>,
+    pub separation_modifier: Option<f32>,
+    pub cohesion_modifier: Option<f32>,
+    pub obstacle_modifier: Option<f32>,
+    pub look_ahead_distance: Option<f32>,
+
+    pub fear_radius: Option<f32>,
+    pub flee_modifier: Option<f32>,
+}
+
+impl ParameterOverrides {
+    /// `base` with every key that is set here replaced.
+    pub fn apply(&self, base: &Parameters) -> Parameters {
+        Parameters {
+            alignment_view_distance: self.alignment_view_distance.unwrap_or(base.alignment_view_distance),
+            separation_view_distance: self.separation_view_distance.unwrap_or(base.separation_view_distance),
+            cohesion_view_distance: self.cohesion_view_distance.unwrap_or(base.cohesion_view_distance),
+            obstacle_radius: self.obstacle_radius.unwrap_or(base.obstacle_radius),
+            alignment_field_of_view: self.alignment_field_of_view.unwrap_or(base.alignment_field_of_view),
+            separation_field_of_view: self.separation_field_of_view.unwrap_or(base.separation_field_of_view),
+            cohesion_field_of_view: self.cohesion_field_of_view.unwrap_or(base.cohesion_field_of_view),
+            obstacle_field_of_view: self.obstacle_field_of_view.unwrap_or(base.obstacle_field_of_view),
+            flee_field_of_view: self.flee_field_of_view.unwrap_or(base.flee_field_of_view),
+            max_speed: self.max_speed.unwrap_or(base.max_speed),
+            max_steering_velocity: self.max_steering_velocity.unwrap_or(base.max_steering_velocity),
+            random_movement: self.random_movement.unwrap_or(base.random_movement),
+            alignment_modifier: self.alignment_modifier.unwrap_or(base.alignment_modifier),
+            separation_modifier: self.separation_modifier.unwrap_or(base.separation_modifier),
+            cohesion_modifier: self.cohesion_modifier.unwrap_or(base.cohesion_modifier),
+            obstacle_modifier: self.obstacle_modifier.unwrap_or(base.obstacle_modifier),
+            look_ahead_distance: self.look_ahead_distance.unwrap_or(base.look_ahead_distance),
+            fear_radius: self.fear_radius.unwrap_or(base.fear_radius),
+            flee_modifier: self.flee_modifier.unwrap_or(base.flee_modifier),
+            ..*base
+        }
+    }
+}
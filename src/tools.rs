@@ -22,4 +22,122 @@ impl Tools{
     pub fn vector_length( vec: &Vector2<f32>) -> f32{
         (vec.x.powf(2.0) + vec.y.powf(2.0)).sqrt()
     }
-}
\ No newline at end of file
+
+    pub fn wrap_position( pos: &mut Point2<f32>, width: f32, height: f32 ) {
+        if pos.x < 0.0 {
+            pos.x += width;
+        }
+        else if pos.x > width {
+            pos.x -= width;
+        }
+        if pos.y < 0.0 {
+            pos.y += height;
+        }
+        else if pos.y > height {
+            pos.y -= height;
+        }
+    }
+
+    /// Whether `direction` lies within the cone of `field_of_view` degrees centred
+    /// on `heading`. Everything is in view without a heading or with 360 degrees.
+    pub fn in_field_of_view( heading: &Vector2<f32>, direction: &Vector2<f32>, field_of_view: f32 ) -> bool {
+        if field_of_view >= 360.0 {
+            return true;
+        }
+        let heading_length = Self::vector_length(heading);
+        let direction_length = Self::vector_length(direction);
+        if heading_length == 0.0 || direction_length == 0.0 {
+            return true;
+        }
+        let cos_angle = heading.dot(direction) / (heading_length * direction_length);
+        cos_angle >= (field_of_view / 2.0).to_radians().cos()
+    }
+
+    /// `to - from` in a `width` by `height` area whose opposite edges touch,
+    /// going the shorter way around on each axis.
+    pub fn wrapped_offset( from: Point2<f32>, to: Point2<f32>, width: f32, height: f32 ) -> Vector2<f32> {
+        let mut offset: Vector2<f32> = to - from;
+        if offset.x > width / 2.0 {
+            offset.x -= width;
+        }
+        else if offset.x < -width / 2.0 {
+            offset.x += width;
+        }
+        if offset.y > height / 2.0 {
+            offset.y -= height;
+        }
+        else if offset.y < -height / 2.0 {
+            offset.y += height;
+        }
+        offset
+    }
+
+    /// Mirrors `pos` back into the area and points `vel` away from the edge it crossed.
+    pub fn bounce_position( pos: &mut Point2<f32>, vel: &mut Vector2<f32>, width: f32, height: f32 ) {
+        if pos.x < 0.0 {
+            pos.x = -pos.x;
+            vel.x = vel.x.abs();
+        }
+        else if pos.x > width {
+            pos.x = 2.0 * width - pos.x;
+            vel.x = -vel.x.abs();
+        }
+        if pos.y < 0.0 {
+            pos.y = -pos.y;
+            vel.y = vel.y.abs();
+        }
+        else if pos.y > height {
+            pos.y = 2.0 * height - pos.y;
+            vel.y = -vel.y.abs();
+        }
+        Tools::clamp_position(pos, width, height);
+    }
+
+    pub fn clamp_position( pos: &mut Point2<f32>, width: f32, height: f32 ) {
+        pos.x = pos.x.max(0.0).min(width);
+        pos.y = pos.y.max(0.0).min(height);
+    }
+
+    pub fn closest_point_on_segment( start: Point2<f32>, end: Point2<f32>, point: Point2<f32> ) -> Point2<f32> {
+        let segment: Vector2<f32> = end - start;
+        let length_squared = segment.x.powf(2.0) + segment.y.powf(2.0);
+        if length_squared == 0.0 {
+            return start;
+        }
+        let t = ((point - start).dot(&segment) / length_squared).clamp(0.0, 1.0);
+        start + segment * t
+    }
+
+    /// Distance along the ray from `origin` in unit direction `dir` to the segment from `start` to `end`.
+    pub fn ray_segment_intersection( origin: Point2<f32>, dir: Vector2<f32>, start: Point2<f32>, end: Point2<f32> ) -> Option<f32> {
+        let segment: Vector2<f32> = end - start;
+        let denominator = dir.x * segment.y - dir.y * segment.x;
+        if denominator.abs() < f32::EPSILON {
+            return None;
+        }
+        let to_start: Vector2<f32> = start - origin;
+        let t = (to_start.x * segment.y - to_start.y * segment.x) / denominator;
+        let u = (to_start.x * dir.y - to_start.y * dir.x) / denominator;
+        if t >= 0.0 && (0.0..=1.0).contains(&u) {
+            Some(t)
+        }
+        else {
+            None
+        }
+    }
+
+    /// Distance along the ray from `origin` in unit direction `dir` to the circle, zero when starting inside.
+    pub fn ray_circle_intersection( origin: Point2<f32>, dir: Vector2<f32>, center: Point2<f32>, radius: f32 ) -> Option<f32> {
+        let from_center: Vector2<f32> = origin - center;
+        let b = from_center.dot(&dir);
+        let c = from_center.dot(&from_center) - radius.powf(2.0);
+        if c > 0.0 && b > 0.0 {
+            return None;
+        }
+        let discriminant = b.powf(2.0) - c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        Some((-b - discriminant.sqrt()).max(0.0))
+    }
+}  
@@ -0,0 +1,230 @@
+use ggez::nalgebra::{Point2, Vector2};
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::simulation::Simulation;
+
+/// First bytes of a binary trajectory file.
+pub const MAGIC: &[u8; 8] = b"BOIDTRJ1";
+
+/// How `TrajectoryRecorder` encodes the birds.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TrajectoryFormat {
+    /// Fixed-size little-endian records. See `TrajectoryRecorder` for the
+    /// layout and `TrajectoryReader` to read it back.
+    Binary,
+    /// One row per bird per tick, with a header naming the columns.
+    Csv,
+}
+
+impl TrajectoryFormat {
+    /// `Csv` for paths ending in `.csv`, `Binary` for anything else.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> TrajectoryFormat {
+        match path.as_ref().extension() {
+            Some(extension) if extension.eq_ignore_ascii_case("csv") => TrajectoryFormat::Csv,
+            _ => TrajectoryFormat::Binary
+        }
+    }
+}
+
+/// The state of one bird at one tick, as recorded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BirdRecord {
+    pub id: u64,
+    pub species: usize,
+    pub pos: Point2<f32>,
+    pub vel: Vector2<f32>,
+    /// One vector per recorded rule, zero for rules the bird has not been steered by yet.
+    pub steering: Vec<Vector2<f32>>,
+}
+
+/// Every bird at one tick.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub tick: u64,
+    pub birds: Vec<BirdRecord>,
+}
+
+/// Writes every bird's id, species, position, velocity and per-rule steering
+/// each time `record` is called, normally once per tick.
+///
+/// The binary format starts with the `MAGIC` bytes, the number of rules as a
+/// `u32` and each rule name as a `u32` length and UTF-8 bytes. Each frame is
+/// then the tick as a `u64` and the bird count as a `u32`, followed per bird
+/// by its id as a `u64`, species as a `u32`, and `f32`s for the position,
+/// velocity and the x and y of each rule's steering. All numbers are little-endian.
+///
+/// The rules are those of the simulation at the first `record` call.
+pub struct TrajectoryRecorder<W: Write> {
+    writer: W,
+    format: TrajectoryFormat,
+    rules: Option<usize>,
+}
+
+impl TrajectoryRecorder<BufWriter<File>> {
+    /// Creates or truncates the file at `path`, in the format its extension implies.
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let format = TrajectoryFormat::from_path(&path);
+        Ok(TrajectoryRecorder::new(BufWriter::new(File::create(path)?), format))
+    }
+}
+
+impl<W: Write> TrajectoryRecorder<W> {
+    pub fn new(writer: W, format: TrajectoryFormat) -> Self {
+        TrajectoryRecorder {
+            writer,
+            format,
+            rules: None,
+        }
+    }
+
+    pub fn record(&mut self, simulation: &Simulation) -> io::Result<()> {
+        let rules = match self.rules {
+            Some(rules) => rules,
+            None => {
+                self.write_header(simulation)?;
+                self.rules = Some(simulation.rules.len());
+                simulation.rules.len()
+            }
+        };
+
+        let steering = |bird_steering: &[Vector2<f32>], rule: usize| bird_steering.get(rule).cloned().unwrap_or_else(|| Vector2::new(0.0, 0.0));
+        match self.format {
+            TrajectoryFormat::Binary => {
+                self.writer.write_all(&simulation.tick.to_le_bytes())?;
+                self.writer.write_all(&(simulation.birds.len() as u32).to_le_bytes())?;
+                for bird in simulation.birds.iter() {
+                    self.writer.write_all(&bird.id.to_le_bytes())?;
+                    self.writer.write_all(&(bird.species as u32).to_le_bytes())?;
+                    for &value in [bird.pos.x, bird.pos.y, bird.vel.x, bird.vel.y].iter() {
+                        self.writer.write_all(&value.to_le_bytes())?;
+                    }
+                    for rule in 0..rules {
+                        let vector = steering(&bird.steering, rule);
+                        self.writer.write_all(&vector.x.to_le_bytes())?;
+                        self.writer.write_all(&vector.y.to_le_bytes())?;
+                    }
+                }
+            },
+            TrajectoryFormat::Csv => {
+                for bird in simulation.birds.iter() {
+                    write!(self.writer, "{},{},{},{},{},{},{}", simulation.tick, bird.id, bird.species, bird.pos.x, bird.pos.y, bird.vel.x, bird.vel.y)?;
+                    for rule in 0..rules {
+                        let vector = steering(&bird.steering, rule);
+                        write!(self.writer, ",{},{}", vector.x, vector.y)?;
+                    }
+                    writeln!(self.writer)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Flushes the recording and returns the writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn write_header(&mut self, simulation: &Simulation) -> io::Result<()> {
+        let names: Vec<&str> = simulation.rules.iter().map(|active| active.rule.name()).collect();
+        match self.format {
+            TrajectoryFormat::Binary => {
+                self.writer.write_all(MAGIC)?;
+                self.writer.write_all(&(names.len() as u32).to_le_bytes())?;
+                for name in names {
+                    self.writer.write_all(&(name.len() as u32).to_le_bytes())?;
+                    self.writer.write_all(name.as_bytes())?;
+                }
+            },
+            TrajectoryFormat::Csv => {
+                write!(self.writer, "tick,id,species,x,y,vx,vy")?;
+                for name in names {
+                    write!(self.writer, ",{}_x,{}_y", name, name)?;
+                }
+                writeln!(self.writer)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads the frames of a binary recording made by `TrajectoryRecorder`.
+pub struct TrajectoryReader<R: Read> {
+    reader: R,
+    rules: Vec<String>,
+}
+
+impl TrajectoryReader<BufReader<File>> {
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        TrajectoryReader::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> TrajectoryReader<R> {
+    /// Reads the header, failing if `reader` is not a binary trajectory.
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a boids trajectory file"));
+        }
+        let count = read_u32(&mut reader)?;
+        let mut rules = Vec::new();
+        for _ in 0..count {
+            let mut name = vec![0; read_u32(&mut reader)? as usize];
+            reader.read_exact(&mut name)?;
+            rules.push(String::from_utf8(name).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?);
+        }
+        Ok(TrajectoryReader { reader, rules })
+    }
+
+    /// Names of the rules whose steering each `BirdRecord` holds, in order.
+    pub fn rules(&self) -> &[String] {
+        &self.rules
+    }
+
+    /// The next frame, or `None` at the end of the recording.
+    pub fn next_frame(&mut self) -> io::Result<Option<Frame>> {
+        let mut tick = [0; 8];
+        match self.reader.read_exact(&mut tick) {
+            Ok(()) => (),
+            Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e)
+        }
+        let count = read_u32(&mut self.reader)?;
+        let mut birds = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let id = read_u64(&mut self.reader)?;
+            let species = read_u32(&mut self.reader)? as usize;
+            let pos = Point2::new(read_f32(&mut self.reader)?, read_f32(&mut self.reader)?);
+            let vel = Vector2::new(read_f32(&mut self.reader)?, read_f32(&mut self.reader)?);
+            let mut steering = Vec::with_capacity(self.rules.len());
+            for _ in 0..self.rules.len() {
+                steering.push(Vector2::new(read_f32(&mut self.reader)?, read_f32(&mut self.reader)?));
+            }
+            birds.push(BirdRecord { id, species, pos, vel, steering });
+        }
+        Ok(Some(Frame { tick: u64::from_le_bytes(tick), birds }))
+    }
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn read_f32<R: Read>(reader: &mut R) -> io::Result<f32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(f32::from_le_bytes(bytes))
+}
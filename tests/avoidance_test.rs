@@ -0,0 +1,152 @@
+use boids::config::Config;
+use boids::entities::{ Bird, Motion, Obstacle, Shape };
+use boids::simulation::{ ObstacleAvoidance, Parameters, Simulation };
+use boids::species::Species;
+use boids::tools::Tools;
+use ggez::nalgebra::{ Point2, Vector2 };
+
+#[test]
+fn test_ray_segment_intersection() {
+    let origin = Point2::new(0.0, 0.0);
+    let right = Vector2::new(1.0, 0.0);
+
+    assert_eq!(Tools::ray_segment_intersection(origin, right, Point2::new(10.0, -5.0), Point2::new(10.0, 5.0)), Some(10.0));
+    assert_eq!(Tools::ray_segment_intersection(origin, right, Point2::new(-10.0, -5.0), Point2::new(-10.0, 5.0)), None);
+    assert_eq!(Tools::ray_segment_intersection(origin, right, Point2::new(10.0, 1.0), Point2::new(10.0, 5.0)), None);
+    assert_eq!(Tools::ray_segment_intersection(origin, right, Point2::new(0.0, 1.0), Point2::new(10.0, 1.0)), None);
+}
+
+#[test]
+fn test_ray_circle_intersection() {
+    let origin = Point2::new(0.0, 0.0);
+    let right = Vector2::new(1.0, 0.0);
+
+    assert_eq!(Tools::ray_circle_intersection(origin, right, Point2::new(20.0, 0.0), 5.0), Some(15.0));
+    assert_eq!(Tools::ray_circle_intersection(origin, right, Point2::new(-20.0, 0.0), 5.0), None);
+    assert_eq!(Tools::ray_circle_intersection(origin, right, Point2::new(20.0, 10.0), 5.0), None);
+    assert_eq!(Tools::ray_circle_intersection(origin, right, Point2::new(1.0, 0.0), 5.0), Some(0.0));
+}
+
+#[test]
+fn test_ray_cast_normal_faces_the_ray() {
+    let (pos, wall) = Shape::wall(Point2::new(10.0, -5.0), Point2::new(10.0, 5.0));
+
+    let (distance, normal) = wall.ray_cast(pos, Point2::new(0.0, 0.0), Vector2::new(1.0, 0.0), 0.0).unwrap();
+
+    assert_eq!(distance, 10.0);
+    assert_eq!(normal, Vector2::new(-1.0, 0.0));
+}
+
+fn evasion_only(avoidance: ObstacleAvoidance) -> Simulation {
+    let mut simulation = Simulation::with_seed(800.0, 600.0, 1);
+    for name in &["alignment", "separation", "cohesion", "random_movement", "flee"] {
+        simulation.set_rule_enabled(name, false);
+    }
+    simulation.parameters.obstacle_avoidance = avoidance;
+    simulation
+}
+
+#[test]
+fn test_look_ahead_reacts_before_radius() {
+    for &avoidance in [ObstacleAvoidance::Radial, ObstacleAvoidance::LookAhead].iter() {
+        let mut simulation = evasion_only(avoidance);
+        let (pos, wall) = Shape::wall(Point2::new(500.0, 100.0), Point2::new(500.0, 500.0));
+        simulation.spawn_shaped_obstacle(pos, wall);
+        // 100 away, outside `obstacle_radius` but inside `look_ahead_distance`
+        simulation.birds.push(Bird::new(Point2::new(400.0, 290.0), Vector2::new(3.0, 0.0)));
+
+        simulation.step(1.0 / 60.0);
+
+        let turned = simulation.birds[0].vel.y != 0.0;
+        assert_eq!(turned, avoidance == ObstacleAvoidance::LookAhead, "{:?}", avoidance);
+    }
+}
+
+#[test]
+fn test_look_ahead_steers_around_the_near_side() {
+    let mut simulation = evasion_only(ObstacleAvoidance::LookAhead);
+    simulation.spawn_obstacle(Point2::new(500.0, 310.0));
+    simulation.birds.push(Bird::new(Point2::new(400.0, 300.0), Vector2::new(3.0, 0.0)));
+
+    simulation.step(1.0 / 60.0);
+
+    // the obstacle is slightly below the heading, so go over it
+    assert!(simulation.birds[0].vel.y < 0.0);
+}
+
+#[test]
+fn test_each_obstacle_has_its_own_radius() {
+    for &avoidance in [ObstacleAvoidance::Radial, ObstacleAvoidance::LookAhead].iter() {
+        let mut simulation = evasion_only(avoidance);
+        simulation.obstacles.push(Obstacle::new(Point2::new(200.0, 150.0), Some(10.0)));
+        simulation.obstacles.push(Obstacle::new(Point2::new(200.0, 400.0), Some(80.0)));
+        // both passing 60 beside an obstacle, beyond the small radius but within the large one
+        simulation.birds.push(Bird::new(Point2::new(200.0, 210.0), Vector2::new(3.0, 0.0)));
+        simulation.birds.push(Bird::new(Point2::new(200.0, 460.0), Vector2::new(3.0, 0.0)));
+
+        simulation.step(1.0 / 60.0);
+
+        // without any steering a bird only speeds up
+        assert_eq!(simulation.birds[0].vel, Vector2::new(3.0, 0.0) * Bird::SELF_ACCELERATION, "{:?}", avoidance);
+        assert!(simulation.birds[1].vel.y > 0.0, "{:?}", avoidance);
+    }
+}
+
+#[test]
+fn test_obstacles_without_a_radius_follow_the_parameters() {
+    for &avoidance in [ObstacleAvoidance::Radial, ObstacleAvoidance::LookAhead].iter() {
+        let mut simulation = evasion_only(avoidance);
+        let mut hawk_shy = Species::new("hawk_shy");
+        hawk_shy.parameters = Some(Parameters { obstacle_radius: 80.0, ..Parameters::default() });
+        simulation.set_species(vec![Species::new("starling"), hawk_shy]);
+        simulation.spawn_obstacle(Point2::new(200.0, 300.0));
+        // passing 60 beside the obstacle, the second bird of the species with the larger radius
+        simulation.birds.push(Bird::new(Point2::new(200.0, 360.0), Vector2::new(3.0, 0.0)));
+        let mut bird = Bird::new(Point2::new(200.0, 360.0), Vector2::new(3.0, 0.0));
+        bird.species = 1;
+        simulation.birds.push(bird);
+
+        simulation.parameters.obstacle_radius = 20.0;
+        simulation.step(1.0 / 60.0);
+        assert_eq!(simulation.obstacles[0].radius, None);
+        assert_eq!(simulation.birds[0].vel, Vector2::new(3.0, 0.0) * Bird::SELF_ACCELERATION, "{:?}", avoidance);
+        assert!(simulation.birds[1].vel.y > 0.0, "{:?}", avoidance);
+
+        // a changed parameter applies to the obstacle already there
+        simulation.birds[0].pos = Point2::new(200.0, 360.0);
+        simulation.birds[0].vel = Vector2::new(3.0, 0.0);
+        simulation.parameters.obstacle_radius = 80.0;
+        simulation.step(1.0 / 60.0);
+        assert!(simulation.birds[0].vel.y > 0.0, "{:?}", avoidance);
+    }
+}
+
+#[test]
+fn test_evasion_expects_obstacles_to_keep_moving() {
+    for &(drifting, evades) in [(false, false), (true, true)].iter() {
+        let mut simulation = evasion_only(ObstacleAvoidance::Radial);
+        if drifting {
+            simulation.spawn_moving_obstacle(Point2::new(400.0, 300.0), Motion::Drift { vel: Vector2::new(10.0, 0.0) });
+        }
+        else {
+            simulation.spawn_obstacle(Point2::new(410.0, 300.0));
+        }
+        // 55 away from where the obstacle is after this tick's update, 45 from
+        // where it will be after the next
+        simulation.birds.push(Bird::new(Point2::new(465.0, 300.0), Vector2::new(-1.0, 0.0)));
+
+        simulation.step(1.0 / 60.0);
+
+        assert_eq!(simulation.obstacles[0].pos, Point2::new(410.0, 300.0));
+        assert_eq!(simulation.birds[0].vel.x > -1.0, evades, "drifting: {}", drifting);
+    }
+}
+
+#[test]
+fn test_avoidance_mode_from_config() {
+    let config = Config::parse("[parameters]\nobstacle_avoidance = \"look_ahead\"\nlook_ahead_distance = 80.0").unwrap();
+
+    assert_eq!(config.parameters.obstacle_avoidance, ObstacleAvoidance::LookAhead);
+    assert_eq!(config.parameters.look_ahead_distance, 80.0);
+    assert!(Config::parse("[parameters]\nobstacle_avoidance = \"magnetic\"").is_err());
+}
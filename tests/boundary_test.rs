@@ -0,0 +1,128 @@
+use boids::cli::Options;
+use boids::entities::Bird;
+use boids::simulation::{ Boundary, Simulation };
+use ggez::nalgebra::{ Point2, Vector2 };
+use structopt::StructOpt;
+
+fn simulation(boundary: Boundary) -> Simulation {
+    let mut simulation = Simulation::with_seed(800.0, 600.0, 1);
+    simulation.set_rule_enabled("random_movement", false);
+    simulation.parameters.boundary = boundary;
+    simulation
+}
+
+#[test]
+fn test_wrap_reenters_on_the_other_side() {
+    let mut simulation = simulation(Boundary::Wrap);
+    simulation.birds.push(Bird::new(Point2::new(799.0, 300.0), Vector2::new(3.0, 0.0)));
+
+    simulation.step(1.0 / 60.0);
+
+    assert!(simulation.birds[0].pos.x < 10.0);
+    assert!(simulation.birds[0].vel.x > 0.0);
+}
+
+#[test]
+fn test_bounce_reflects_off_the_edge() {
+    let mut simulation = simulation(Boundary::Bounce);
+    simulation.birds.push(Bird::new(Point2::new(799.0, 300.0), Vector2::new(3.0, 0.0)));
+
+    simulation.step(1.0 / 60.0);
+
+    assert!(simulation.birds[0].pos.x <= 800.0 && simulation.birds[0].pos.x > 790.0);
+    assert!(simulation.birds[0].vel.x < 0.0);
+}
+
+#[test]
+fn test_kill_removes_birds_that_leave() {
+    let mut simulation = simulation(Boundary::Kill);
+    simulation.birds.push(Bird::new(Point2::new(799.0, 300.0), Vector2::new(3.0, 0.0)));
+    simulation.birds.push(Bird::new(Point2::new(400.0, 300.0), Vector2::new(3.0, 0.0)));
+
+    simulation.step(1.0 / 60.0);
+
+    assert_eq!(simulation.birds.len(), 1);
+    assert!(simulation.birds[0].pos.x > 400.0);
+}
+
+#[test]
+fn test_contain_turns_birds_back_near_the_edge() {
+    let mut simulation = simulation(Boundary::Contain);
+    simulation.birds.push(Bird::new(Point2::new(780.0, 300.0), Vector2::new(3.0, 0.0)));
+    simulation.birds.push(Bird::new(Point2::new(400.0, 100.0), Vector2::new(3.0, 0.0)));
+
+    simulation.step(1.0 / 60.0);
+    assert!(simulation.birds[0].vel.x < 3.0);
+    assert_eq!(simulation.birds[1].vel, Vector2::new(3.0 * Bird::SELF_ACCELERATION, 0.0));
+
+    for _ in 0..100 {
+        simulation.step(1.0 / 60.0);
+        assert!(simulation.birds[0].pos.x <= 800.0);
+    }
+}
+
+#[test]
+fn test_containment_does_nothing_in_other_modes() {
+    let mut simulation = simulation(Boundary::Bounce);
+    simulation.birds.push(Bird::new(Point2::new(780.0, 300.0), Vector2::new(3.0, 0.0)));
+
+    simulation.step(1.0 / 60.0);
+
+    assert_eq!(simulation.birds[0].vel, Vector2::new(3.0 * Bird::SELF_ACCELERATION, 0.0));
+}
+
+#[test]
+fn test_boundary_modes_cycle() {
+    let mut boundary = Boundary::Wrap;
+    for name in Boundary::NAMES {
+        assert_eq!(boundary, name.parse().unwrap());
+        assert_eq!(boundary.to_string(), *name);
+        boundary = boundary.next();
+    }
+    assert_eq!(boundary, Boundary::Wrap);
+}
+
+#[test]
+fn test_boundary_option_overrides_config() {
+    let options = Options::from_iter_safe(vec!["boids", "--boundary", "bounce"]).unwrap();
+
+    assert_eq!(options.load_config().unwrap().parameters.boundary, Boundary::Bounce);
+    assert!(Options::from_iter_safe(vec!["boids", "--boundary", "sticky"]).is_err());
+}
+
+#[test]
+fn test_wrap_sees_neighbours_across_the_edge() {
+    for &boundary in [Boundary::Wrap, Boundary::Bounce].iter() {
+        let mut simulation = simulation(boundary);
+        simulation.set_rule_enabled("alignment", false);
+        simulation.set_rule_enabled("cohesion", false);
+        simulation.birds.push(Bird::new(Point2::new(5.0, 300.0), Vector2::new(0.0, 0.0)));
+        simulation.birds.push(Bird::new(Point2::new(795.0, 300.0), Vector2::new(0.0, 0.0)));
+
+        simulation.step(1.0 / 60.0);
+
+        if boundary == Boundary::Wrap {
+            // 10 apart across the edge, so they push each other away from it
+            assert!(simulation.birds[0].vel.x > 0.0);
+            assert!(simulation.birds[1].vel.x < 0.0);
+        }
+        else {
+            assert_eq!(simulation.birds[0].vel.x, 0.0);
+        }
+    }
+}
+
+#[test]
+fn test_cohesion_centre_across_the_edge() {
+    let mut simulation = simulation(Boundary::Wrap);
+    simulation.set_rule_enabled("alignment", false);
+    simulation.set_rule_enabled("separation", false);
+    simulation.birds.push(Bird::new(Point2::new(40.0, 300.0), Vector2::new(0.0, 0.0)));
+    simulation.birds.push(Bird::new(Point2::new(780.0, 300.0), Vector2::new(0.0, 0.0)));
+
+    simulation.step(1.0 / 60.0);
+
+    // the centre is at the edge, not in the middle of the screen
+    assert!(simulation.birds[0].vel.x < 0.0);
+    assert!(simulation.birds[1].vel.x > 0.0);
+}
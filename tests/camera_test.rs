@@ -0,0 +1,75 @@
+use boids::camera::Camera;
+use ggez::nalgebra::{ Point2, Vector2 };
+
+fn assert_close(a: Point2<f32>, b: Point2<f32>) {
+    assert!((a.x - b.x).abs() < 1e-3 && (a.y - b.y).abs() < 1e-3, "{:?} != {:?}", a, b);
+}
+
+#[test]
+fn test_new_camera_maps_pixels_to_world_units() {
+    let camera = Camera::new(800.0, 600.0);
+    for &point in [Point2::new(0.0, 0.0), Point2::new(400.0, 300.0), Point2::new(799.0, 17.5)].iter() {
+        assert_close(camera.screen_to_world(point), point);
+        assert_close(camera.world_to_screen(point), point);
+    }
+    let rect = camera.world_rect();
+    assert_eq!((rect.x, rect.y, rect.w, rect.h), (0.0, 0.0, 800.0, 600.0));
+}
+
+#[test]
+fn test_transforms_are_inverse() {
+    let mut camera = Camera::new(800.0, 600.0);
+    camera.zoom_at(Point2::new(123.0, 456.0), 2.5);
+    camera.pan(Vector2::new(-40.0, 75.0));
+    for &point in [Point2::new(0.0, 0.0), Point2::new(-250.0, 1300.0), Point2::new(640.0, 12.0)].iter() {
+        assert_close(camera.world_to_screen(camera.screen_to_world(point)), point);
+        assert_close(camera.screen_to_world(camera.world_to_screen(point)), point);
+    }
+
+    // the world rect is exactly what the window shows
+    let rect = camera.world_rect();
+    assert_close(Point2::new(rect.x, rect.y), camera.screen_to_world(Point2::new(0.0, 0.0)));
+    assert_close(Point2::new(rect.x + rect.w, rect.y + rect.h), camera.screen_to_world(Point2::new(800.0, 600.0)));
+}
+
+#[test]
+fn test_zoom_keeps_the_point_under_the_mouse() {
+    let mut camera = Camera::new(800.0, 600.0);
+    let mouse = Point2::new(600.0, 150.0);
+    let before = camera.screen_to_world(mouse);
+    camera.zoom_at(mouse, 2.0);
+    assert_eq!(camera.zoom, 2.0);
+    assert_close(camera.screen_to_world(mouse), before);
+
+    // a world distance of 10 now spans 20 pixels
+    let a = camera.world_to_screen(Point2::new(100.0, 100.0));
+    let b = camera.world_to_screen(Point2::new(110.0, 100.0));
+    assert!((b.x - a.x - 20.0).abs() < 1e-3);
+
+    camera.zoom_at(mouse, 1000.0);
+    assert_eq!(camera.zoom, Camera::MAX_ZOOM);
+    camera.zoom_at(mouse, 0.0);
+    assert_eq!(camera.zoom, Camera::MIN_ZOOM);
+    assert_close(camera.screen_to_world(mouse), before);
+}
+
+#[test]
+fn test_pan_drags_the_world_with_the_mouse() {
+    let mut camera = Camera::new(800.0, 600.0);
+    camera.zoom_at(Point2::new(400.0, 300.0), 4.0);
+    let grabbed = camera.screen_to_world(Point2::new(200.0, 200.0));
+    camera.pan(Vector2::new(100.0, -50.0));
+    assert_close(camera.screen_to_world(Point2::new(300.0, 150.0)), grabbed);
+}
+
+#[test]
+fn test_resize_keeps_the_center() {
+    let mut camera = Camera::new(800.0, 600.0);
+    camera.pan(Vector2::new(30.0, 30.0));
+    let center = camera.screen_to_world(Point2::new(400.0, 300.0));
+    camera.resize(1000.0, 400.0);
+    assert_eq!(camera.screen_size(), (1000.0, 400.0));
+    assert_close(camera.screen_to_world(Point2::new(500.0, 200.0)), center);
+    let rect = camera.screen_rect();
+    assert_eq!((rect.x, rect.y, rect.w, rect.h), (0.0, 0.0, 1000.0, 400.0));
+}
@@ -0,0 +1,89 @@
+use boids::cli::Options;
+use boids::simulation::{ Simulation, SpawnPattern };
+use structopt::StructOpt;
+
+fn options(args: &[&str]) -> Options {
+    Options::from_iter_safe(std::iter::once("boids").chain(args.iter().cloned())).unwrap()
+}
+
+#[test]
+fn test_defaults() {
+    let options = options(&[]);
+
+    assert_eq!(options.birds, 1);
+    assert_eq!(options.spawn, SpawnPattern::Center);
+    assert!(!options.headless);
+    assert_eq!(options.steps, 1000);
+    assert_eq!(options.metrics, None);
+    assert_eq!(options.metrics_interval, 10);
+}
+
+#[test]
+fn test_headless_run_arguments() {
+    let options = options(&["--headless", "--steps", "50", "--birds", "20", "--spawn", "grid", "--seed", "7"]);
+
+    assert!(options.headless);
+    assert_eq!(options.steps, 50);
+    assert_eq!(options.birds, 20);
+    assert_eq!(options.spawn, SpawnPattern::Grid);
+    assert_eq!(options.seed, Some(7));
+}
+
+#[test]
+fn test_unknown_spawn_pattern_is_rejected() {
+    let result = Options::from_iter_safe(vec!["boids", "--spawn", "spiral"]);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_window_overrides_config() {
+    let config_path = concat!(env!("CARGO_MANIFEST_DIR"), "/boids.toml");
+    let options = options(&["--config", config_path, "--width", "640", "--height", "480", "--windowed"]);
+    let config = options.load_config().unwrap();
+
+    assert_eq!(config.window.width, 640.0);
+    assert_eq!(config.window.height, 480.0);
+    assert!(!config.window.fullscreen);
+}
+
+#[test]
+fn test_missing_config_file_is_an_error() {
+    let options = options(&["--config", "does/not/exist.toml"]);
+
+    assert!(options.load_config().is_err());
+}
+
+#[test]
+fn test_simulation_spawns_requested_flock() {
+    let options = options(&["--birds", "25", "--spawn", "random", "--seed", "3"]);
+    let config = options.load_config().unwrap();
+    let simulation = options.simulation(&config, None);
+
+    assert_eq!(simulation.seed, 3);
+    assert_eq!(simulation.birds.len(), 25);
+}
+
+#[test]
+fn test_spawn_patterns_stay_inside_world() {
+    for name in SpawnPattern::NAMES {
+        let mut simulation = Simulation::with_seed(400.0, 300.0, 1);
+        simulation.spawn_flock(30, name.parse().unwrap());
+
+        assert_eq!(simulation.birds.len(), 30);
+        for bird in simulation.birds.iter() {
+            assert!(bird.pos.x >= 0.0 && bird.pos.x <= 400.0, "{} {:?}", name, bird.pos);
+            assert!(bird.pos.y >= 0.0 && bird.pos.y <= 300.0, "{} {:?}", name, bird.pos);
+        }
+    }
+}
+
+#[test]
+fn test_world_size_is_independent_of_the_window() {
+    let options = options(&["--width", "800", "--height", "600", "--world-width", "3000", "--seed", "1"]);
+    let config = options.load_config().unwrap();
+    let simulation = options.simulation(&config, None);
+
+    assert_eq!(config.window.width, 800.0);
+    assert_eq!((simulation.width, simulation.height), (3000.0, 600.0));
+}
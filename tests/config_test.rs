@@ -0,0 +1,156 @@
+use boids::config::{ Config, ConfigError, ConfigWatcher };
+use boids::simulation::{ Boundary, Parameters, Simulation };
+
+#[test]
+fn test_empty_config_uses_defaults() {
+    let config = Config::parse("").unwrap();
+
+    assert_eq!(config, Config::default());
+    assert_eq!(config.parameters, Parameters::default());
+}
+
+#[test]
+fn test_partial_config_overrides_keys() {
+    let config = Config::parse(r#"
+        [window]
+        width = 800.0
+        fullscreen = false
+
+        [parameters]
+        max_speed = 5.0
+
+        [rules]
+        cohesion = false
+    "#).unwrap();
+
+    assert_eq!(config.window.width, 800.0);
+    assert_eq!(config.window.height, 1080.0);
+    assert!(!config.window.fullscreen);
+    assert_eq!(config.parameters.max_speed, 5.0);
+    assert_eq!(config.parameters.alignment_modifier, Parameters::ALIGNMENT_MODIFIER);
+    assert!(!config.rules.cohesion);
+    assert!(config.rules.alignment);
+}
+
+#[test]
+fn test_shipped_config_is_valid() {
+    let config = Config::load(concat!(env!("CARGO_MANIFEST_DIR"), "/boids.toml")).unwrap();
+
+    assert_eq!(config.parameters, Parameters::default());
+}
+
+#[test]
+fn test_invalid_value_names_key() {
+    let error = Config::parse("[parameters]\nmax_speed = -1.0").unwrap_err();
+
+    match &error {
+        ConfigError::Invalid { key, .. } => assert_eq!(key, "parameters.max_speed"),
+        _ => panic!("unexpected error {:?}", error),
+    }
+    assert!(error.to_string().contains("parameters.max_speed"));
+}
+
+#[test]
+fn test_world_size_defaults_to_window() {
+    let config = Config::parse("[window]\nwidth = 800.0\nheight = 600.0").unwrap();
+    assert_eq!(config.world_size(), (800.0, 600.0));
+
+    let config = Config::parse("[window]\nwidth = 800.0\nheight = 600.0\n[world]\nwidth = 4000.0\nheight = 3000.0").unwrap();
+    assert_eq!(config.world_size(), (4000.0, 3000.0));
+
+    match Config::parse("[world]\nheight = 0.0").unwrap_err() {
+        ConfigError::Invalid { key, .. } => assert_eq!(key, "world.height"),
+        error => panic!("unexpected error {:?}", error),
+    }
+}
+
+#[test]
+fn test_wrong_type_names_key() {
+    let error = Config::parse("[parameters]\ncohesion_modifier = \"strong\"").unwrap_err();
+
+    assert!(error.to_string().contains("cohesion_modifier"), "{}", error);
+}
+
+#[test]
+fn test_unknown_key_is_rejected() {
+    let error = Config::parse("[parameters]\nmax_sped = 3.0").unwrap_err();
+
+    assert!(error.to_string().contains("max_sped"), "{}", error);
+}
+
+#[test]
+fn test_watcher_reloads_changed_file() {
+    let path = std::env::temp_dir().join(format!("boids_watcher_test_{}.toml", std::process::id()));
+    std::fs::write(&path, "[parameters]\nmax_speed = 2.0").unwrap();
+    let mut watcher = ConfigWatcher::new(&path);
+
+    assert!(watcher.check().is_none());
+
+    // make sure the modification time moves even on coarse file systems
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    std::fs::write(&path, "[parameters]\nmax_speed = 4.0").unwrap();
+    let config = watcher.check().unwrap().unwrap();
+    assert_eq!(config.parameters.max_speed, 4.0);
+    assert!(watcher.check().is_none());
+
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    std::fs::write(&path, "[parameters]\nmax_speed = fast").unwrap();
+    assert!(watcher.check().unwrap().is_err());
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_unknown_rule_in_order_is_rejected() {
+    let error = Config::parse("[rules]\norder = [\"alignment\", \"gravity\"]").unwrap_err();
+
+    match &error {
+        ConfigError::Invalid { key, .. } => assert_eq!(key, "rules.order"),
+        _ => panic!("unexpected error {:?}", error),
+    }
+}
+
+#[test]
+fn test_reapply_only_copies_changed_keys() {
+    let previous = Config::parse(r#"
+        [parameters]
+        max_speed = 4.0
+        cohesion_modifier = 1.0
+
+        [[species]]
+        name = "starling"
+        [species.parameters]
+        max_speed = 3.0
+    "#).unwrap();
+    let mut simulation = Simulation::new(800.0, 600.0);
+    simulation.parameters = previous.parameters;
+    simulation.set_species(previous.species());
+
+    // changed while running
+    simulation.parameters.boundary = Boundary::Bounce;
+    simulation.parameters.alignment_modifier = 3.0;
+    simulation.species[0].parameters.as_mut().unwrap().alignment_modifier = 3.0;
+
+    let next = Config::parse(r#"
+        [parameters]
+        max_speed = 4.0
+        cohesion_modifier = 2.0
+
+        [[species]]
+        name = "starling"
+        color = [255, 0, 0]
+        [species.parameters]
+        max_speed = 2.0
+    "#).unwrap();
+    next.reapply(&previous, &mut simulation).unwrap();
+
+    assert_eq!(simulation.parameters.cohesion_modifier, 2.0);
+    assert_eq!(simulation.parameters.boundary, Boundary::Bounce);
+    assert_eq!(simulation.parameters.alignment_modifier, 3.0);
+    let species = &simulation.species[0];
+    assert_eq!(species.color, (255, 0, 0));
+    let parameters = species.parameters.unwrap();
+    assert_eq!(parameters.max_speed, 2.0);
+    assert_eq!(parameters.cohesion_modifier, 2.0);
+    assert_eq!(parameters.alignment_modifier, 3.0);
+}
@@ -0,0 +1,53 @@
+use boids::config::Config;
+use boids::entities::Bird;
+use boids::simulation::Simulation;
+use boids::tools::Tools;
+use ggez::nalgebra::{ Point2, Vector2 };
+
+#[test]
+fn test_in_field_of_view() {
+    let heading = Vector2::new(1.0, 0.0);
+
+    assert!(Tools::in_field_of_view(&heading, &Vector2::new(1.0, 1.0), 180.0));
+    assert!(!Tools::in_field_of_view(&heading, &Vector2::new(-1.0, 0.1), 270.0));
+    assert!(Tools::in_field_of_view(&heading, &Vector2::new(-1.0, 0.1), 360.0));
+    assert!(Tools::in_field_of_view(&heading, &Vector2::new(0.0, 1.0), 181.0));
+    assert!(!Tools::in_field_of_view(&heading, &Vector2::new(0.0, 1.0), 179.0));
+    // without a heading there is no blind spot
+    assert!(Tools::in_field_of_view(&Vector2::new(0.0, 0.0), &Vector2::new(-1.0, 0.0), 90.0));
+}
+
+fn follower(alignment_field_of_view: f32) -> Simulation {
+    let mut simulation = Simulation::with_seed(800.0, 600.0, 1);
+    for name in &["separation", "cohesion", "random_movement"] {
+        simulation.set_rule_enabled(name, false);
+    }
+    simulation.parameters.alignment_field_of_view = alignment_field_of_view;
+    // the second bird is right behind the first one and heading down
+    simulation.birds.push(Bird::new(Point2::new(400.0, 300.0), Vector2::new(1.0, 0.0)));
+    simulation.birds.push(Bird::new(Point2::new(350.0, 300.0), Vector2::new(0.0, 1.0)));
+    simulation
+}
+
+#[test]
+fn test_neighbours_in_the_blind_spot_are_not_seen() {
+    let mut full = follower(360.0);
+    let mut blind = follower(270.0);
+
+    full.step(1.0 / 60.0);
+    blind.step(1.0 / 60.0);
+
+    assert!(full.birds[0].vel.y > 0.0);
+    assert_eq!(blind.birds[0].vel.y, 0.0);
+    // the bird behind still sees the one ahead
+    assert!(blind.birds[1].vel.x > 0.0);
+}
+
+#[test]
+fn test_field_of_view_is_validated() {
+    let config = Config::parse("[parameters]\nalignment_field_of_view = 270.0").unwrap();
+    assert_eq!(config.parameters.alignment_field_of_view, 270.0);
+
+    let error = Config::parse("[parameters]\ncohesion_field_of_view = 400.0").unwrap_err();
+    assert!(error.to_string().contains("parameters.cohesion_field_of_view"), "{}", error);
+}
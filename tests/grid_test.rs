@@ -0,0 +1,64 @@
+use boids::grid::SpatialGrid;
+use ggez::nalgebra::Point2;
+
+#[test]
+fn test_query_returns_sorted_nearby_indices() {
+    let mut grid = SpatialGrid::new(300.0, 300.0, 100.0);
+    grid.rebuild(vec![
+        Point2::new(250.0, 250.0),
+        Point2::new(10.0, 10.0),
+        Point2::new(150.0, 150.0),
+        Point2::new(120.0, 80.0),
+    ]);
+
+    let mut result = Vec::new();
+    grid.query(Point2::new(50.0, 50.0), 100.0, &mut result);
+
+    assert_eq!(result, vec![1, 2, 3]);
+}
+
+#[test]
+fn test_relocate_moves_index_between_cells() {
+    let mut grid = SpatialGrid::new(300.0, 300.0, 100.0);
+    grid.rebuild(vec![Point2::new(10.0, 10.0)]);
+    grid.relocate(0, Point2::new(10.0, 10.0), Point2::new(290.0, 290.0));
+
+    let mut result = Vec::new();
+    grid.query(Point2::new(10.0, 10.0), 50.0, &mut result);
+    assert!(result.is_empty());
+
+    grid.query(Point2::new(280.0, 280.0), 50.0, &mut result);
+    assert_eq!(result, vec![0]);
+}
+
+#[test]
+fn test_out_of_bounds_positions_are_clamped() {
+    let mut grid = SpatialGrid::new(300.0, 300.0, 100.0);
+    grid.rebuild(vec![Point2::new(-20.0, 310.0)]);
+
+    let mut result = Vec::new();
+    grid.query(Point2::new(0.0, 299.0), 30.0, &mut result);
+
+    assert_eq!(result, vec![0]);
+}
+
+#[test]
+fn test_wrapped_query_finds_entries_across_edges() {
+    let mut grid = SpatialGrid::new(1000.0, 500.0, 150.0);
+    grid.rebuild(vec![
+        Point2::new(5.0, 250.0),
+        Point2::new(920.0, 250.0),
+        Point2::new(500.0, 250.0),
+        Point2::new(995.0, 495.0),
+    ]);
+
+    let mut result = Vec::new();
+    grid.query(Point2::new(5.0, 250.0), 100.0, &mut result);
+    assert_eq!(result, vec![0]);
+
+    grid.query_wrapped(Point2::new(5.0, 250.0), 100.0, 1000.0, 500.0, &mut result);
+    assert_eq!(result, vec![0, 1]);
+
+    grid.query_wrapped(Point2::new(5.0, 5.0), 20.0, 1000.0, 500.0, &mut result);
+    assert_eq!(result, vec![3]);
+}
@@ -0,0 +1,115 @@
+use boids::entities::Bird;
+use boids::metrics::MetricsLog;
+use boids::simulation::{ Boundary, Simulation };
+use ggez::nalgebra::{ Point2, Vector2 };
+
+use std::f32::consts::PI;
+
+fn simulation(boundary: Boundary, birds: Vec<Bird>) -> Simulation {
+    let mut simulation = Simulation::with_seed(800.0, 600.0, 1);
+    simulation.parameters.boundary = boundary;
+    simulation.birds = birds;
+    simulation
+}
+
+fn assert_close(actual: f32, expected: f32) {
+    assert!((actual - expected).abs() < 1e-3, "expected {}, got {}", expected, actual);
+}
+
+#[test]
+fn test_empty_flock() {
+    let metrics = simulation(Boundary::Wrap, Vec::new()).metrics();
+
+    assert_eq!(metrics.birds, 0);
+    assert_eq!(metrics.polarization, 0.0);
+    assert_eq!(metrics.mean_nearest_neighbour_distance, 0.0);
+    assert_eq!(metrics.center, Point2::new(400.0, 300.0));
+}
+
+#[test]
+fn test_polarization_and_speed() {
+    let aligned = simulation(Boundary::Bounce, vec![
+        Bird::new(Point2::new(100.0, 100.0), Vector2::new(2.0, 0.0)),
+        Bird::new(Point2::new(120.0, 100.0), Vector2::new(4.0, 0.0)),
+    ]).metrics();
+    let opposed = simulation(Boundary::Bounce, vec![
+        Bird::new(Point2::new(100.0, 100.0), Vector2::new(2.0, 0.0)),
+        Bird::new(Point2::new(120.0, 100.0), Vector2::new(-2.0, 0.0)),
+    ]).metrics();
+
+    assert_close(aligned.polarization, 1.0);
+    assert_close(aligned.mean_speed, 3.0);
+    assert_close(opposed.polarization, 0.0);
+}
+
+#[test]
+fn test_milling() {
+    let birds = (0..12).map(|i| {
+        let angle = i as f32 / 12.0 * 2.0 * PI;
+        let pos = Point2::new(400.0 + 100.0 * angle.cos(), 300.0 + 100.0 * angle.sin());
+        Bird::new(pos, Vector2::new(-angle.sin(), angle.cos()) * 3.0)
+    }).collect();
+    let metrics = simulation(Boundary::Bounce, birds).metrics();
+
+    assert_close(metrics.milling, 1.0);
+    assert_close(metrics.polarization, 0.0);
+    assert_close(metrics.radius, 100.0);
+    assert_close(metrics.center.x, 400.0);
+    assert_close(metrics.center.y, 300.0);
+}
+
+#[test]
+fn test_mean_nearest_neighbour_distance() {
+    let birds = vec![
+        Bird::new(Point2::new(100.0, 300.0), Vector2::new(1.0, 0.0)),
+        Bird::new(Point2::new(120.0, 300.0), Vector2::new(1.0, 0.0)),
+        Bird::new(Point2::new(400.0, 300.0), Vector2::new(1.0, 0.0)),
+    ];
+    let metrics = simulation(Boundary::Bounce, birds).metrics();
+
+    assert_close(metrics.mean_nearest_neighbour_distance, (20.0 + 20.0 + 280.0) / 3.0);
+}
+
+#[test]
+fn test_metrics_across_a_wrapping_edge() {
+    let birds = vec![
+        Bird::new(Point2::new(5.0, 300.0), Vector2::new(1.0, 0.0)),
+        Bird::new(Point2::new(795.0, 300.0), Vector2::new(1.0, 0.0)),
+    ];
+    let wrapped = simulation(Boundary::Wrap, birds.clone()).metrics();
+    let bounded = simulation(Boundary::Bounce, birds).metrics();
+
+    assert_close(wrapped.mean_nearest_neighbour_distance, 10.0);
+    assert_close(wrapped.radius, 5.0);
+    assert!(wrapped.center.x < 1e-3 || wrapped.center.x > 800.0 - 1e-3, "{}", wrapped.center.x);
+    assert_close(bounded.mean_nearest_neighbour_distance, 790.0);
+    assert_close(bounded.center.x, 400.0);
+}
+
+#[test]
+fn test_metrics_log_writes_every_interval() {
+    let mut simulation = Simulation::with_seed(800.0, 600.0, 42);
+    simulation.set_rule_enabled("cohesion", false);
+    simulation.birds.push(Bird::new(Point2::new(100.0, 100.0), Vector2::new(1.0, 0.0)));
+    let mut log = MetricsLog::new(Vec::new(), 5);
+
+    log.record(&simulation).unwrap();
+    for _ in 0..12 {
+        simulation.step(1.0 / 60.0);
+        log.record(&simulation).unwrap();
+    }
+
+    let csv = String::from_utf8(log.into_inner()).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[0], "tick,time,seed,birds,polarization,milling,mean_nearest_neighbour_distance,radius,mean_speed,center_x,center_y,\
+alignment,separation,cohesion,random_movement,obstacle_evasion,flee,containment");
+
+    let ticks: Vec<&str> = lines[1..].iter().map(|line| line.split(',').next().unwrap()).collect();
+    assert_eq!(ticks, vec!["0", "5", "10"]);
+    let row: Vec<&str> = lines[2].split(',').collect();
+    assert_eq!(row.len(), 18);
+    assert_eq!(row[2], "42");
+    assert_eq!(row[3], "1");
+    assert_eq!(&row[11..14], &["1", "1", "0"]);
+}
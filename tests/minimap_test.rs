@@ -0,0 +1,57 @@
+use boids::camera::Camera;
+use boids::minimap::Minimap;
+use boids::simulation::Simulation;
+use ggez::nalgebra::Point2;
+
+fn assert_close(a: Point2<f32>, b: Point2<f32>) {
+    assert!((a.x - b.x).abs() < 1e-3 && (a.y - b.y).abs() < 1e-3, "{:?} != {:?}", a, b);
+}
+
+#[test]
+fn test_minimap_keeps_world_proportions_in_the_corner() {
+    let simulation = Simulation::with_seed(4000.0, 1000.0, 1);
+    let camera = Camera::new(800.0, 600.0);
+    let minimap = Minimap::new();
+    let rect = minimap.rect(&camera, &simulation);
+
+    assert_eq!((rect.w, rect.h), (Minimap::SIZE, Minimap::SIZE / 4.0));
+    assert_eq!(rect.x + rect.w, 800.0 - Minimap::MARGIN);
+    assert_eq!(rect.y + rect.h, 600.0 - Minimap::MARGIN);
+    assert_eq!(Minimap::cells(&simulation), (Minimap::CELLS, Minimap::CELLS / 4));
+}
+
+#[test]
+fn test_minimap_points_map_to_the_world() {
+    let simulation = Simulation::with_seed(3000.0, 2000.0, 1);
+    let camera = Camera::new(800.0, 600.0);
+    let mut minimap = Minimap::new();
+    let rect = minimap.rect(&camera, &simulation);
+
+    assert_close(minimap.minimap_to_world(&camera, &simulation, Point2::new(rect.x, rect.y)), Point2::new(0.0, 0.0));
+    assert_close(minimap.minimap_to_world(&camera, &simulation, Point2::new(rect.x + rect.w / 2.0, rect.y + rect.h)), Point2::new(1500.0, 2000.0));
+    let world = Point2::new(700.0, 1234.0);
+    assert_close(minimap.minimap_to_world(&camera, &simulation, minimap.world_to_minimap(&camera, &simulation, world)), world);
+
+    assert!(minimap.contains(&camera, &simulation, Point2::new(rect.x + 1.0, rect.y + 1.0)));
+    assert!(!minimap.contains(&camera, &simulation, Point2::new(10.0, 10.0)));
+    minimap.visible = false;
+    assert!(!minimap.contains(&camera, &simulation, Point2::new(rect.x + 1.0, rect.y + 1.0)));
+}
+
+#[test]
+fn test_density_counts_birds_per_cell() {
+    let mut simulation = Simulation::with_seed(400.0, 200.0, 1);
+    simulation.spawn_bird(Point2::new(10.0, 10.0));
+    simulation.spawn_bird(Point2::new(90.0, 90.0));
+    simulation.spawn_bird(Point2::new(390.0, 150.0));
+    // outside the world, counted in the nearest cell
+    simulation.spawn_bird(Point2::new(-5.0, 250.0));
+    simulation.spawn_bird(Point2::new(400.0, 200.0));
+
+    let counts = Minimap::density(&simulation, 4, 2);
+    assert_eq!(counts, vec![
+        2, 0, 0, 0,
+        1, 0, 0, 2,
+    ]);
+    assert_eq!(counts.iter().sum::<usize>(), simulation.birds.len());
+}
@@ -0,0 +1,117 @@
+use boids::cli::Options;
+use boids::config::Config;
+use boids::entities::Bird;
+use boids::simulation::{ Boundary, NeighbourSearch, Neighbourhood, Simulation };
+use ggez::nalgebra::{ Point2, Vector2 };
+use structopt::StructOpt;
+
+fn aligning(neighbourhood: Neighbourhood, k: usize) -> Simulation {
+    let mut simulation = Simulation::with_seed(800.0, 600.0, 1);
+    for name in &["separation", "cohesion", "random_movement"] {
+        simulation.set_rule_enabled(name, false);
+    }
+    simulation.parameters.neighbourhood = neighbourhood;
+    simulation.parameters.topological_neighbours = k;
+    simulation
+}
+
+#[test]
+fn test_topological_sees_birds_beyond_view_distance() {
+    let mut metric = aligning(Neighbourhood::Metric, 1);
+    metric.birds.push(Bird::new(Point2::new(100.0, 300.0), Vector2::new(1.0, 0.0)));
+    metric.birds.push(Bird::new(Point2::new(400.0, 300.0), Vector2::new(0.0, 1.0)));
+    let mut topological = aligning(Neighbourhood::Topological, 1);
+    topological.birds = metric.birds.clone();
+
+    metric.step(1.0 / 60.0);
+    topological.step(1.0 / 60.0);
+
+    assert_eq!(metric.birds[0].vel.y, 0.0);
+    assert!(topological.birds[0].vel.y > 0.0);
+}
+
+#[test]
+fn test_topological_sees_only_the_k_nearest() {
+    let birds = vec![
+        Bird::new(Point2::new(400.0, 300.0), Vector2::new(1.0, 0.0)),
+        Bird::new(Point2::new(400.0, 320.0), Vector2::new(0.0, 1.0)),
+        Bird::new(Point2::new(400.0, 260.0), Vector2::new(0.0, -1.0)),
+        Bird::new(Point2::new(340.0, 300.0), Vector2::new(0.0, -1.0)),
+    ];
+    let mut one = aligning(Neighbourhood::Topological, 1);
+    one.birds = birds.clone();
+    let mut three = aligning(Neighbourhood::Topological, 3);
+    three.birds = birds;
+
+    one.step(1.0 / 60.0);
+    three.step(1.0 / 60.0);
+
+    assert!(one.birds[0].vel.y > 0.0);
+    assert!(three.birds[0].vel.y < 0.0);
+}
+
+fn assert_grid_matches_brute_force(boundary: Boundary) {
+    let mut grid = aligning(Neighbourhood::Topological, 7);
+    grid.set_rule_enabled("separation", true);
+    grid.set_rule_enabled("cohesion", true);
+    grid.parameters.boundary = boundary;
+    for i in 0..150 {
+        let x = (i * 37 % 800) as f32;
+        let y = (i * 91 % 600) as f32;
+        let vel = Vector2::new(((i % 7) as f32 - 3.0) * 0.5, ((i % 5) as f32 - 2.0) * 0.5);
+        grid.birds.push(Bird::new(Point2::new(x, y), vel));
+    }
+    let mut brute_force = aligning(Neighbourhood::Topological, 7);
+    brute_force.set_rule_enabled("separation", true);
+    brute_force.set_rule_enabled("cohesion", true);
+    brute_force.parameters.boundary = boundary;
+    brute_force.neighbour_search = NeighbourSearch::BruteForce;
+    brute_force.birds = grid.birds.clone();
+
+    for _ in 0..30 {
+        grid.step(1.0 / 60.0);
+        brute_force.step(1.0 / 60.0);
+    }
+
+    for (a, b) in grid.birds.iter().zip(brute_force.birds.iter()) {
+        assert_eq!(a.pos, b.pos);
+        assert_eq!(a.vel, b.vel);
+    }
+}
+
+#[test]
+fn test_topological_grid_matches_brute_force() {
+    assert_grid_matches_brute_force(Boundary::Bounce);
+}
+
+#[test]
+fn test_topological_grid_matches_brute_force_wrapped() {
+    assert_grid_matches_brute_force(Boundary::Wrap);
+}
+
+#[test]
+fn test_fewer_birds_than_k() {
+    let mut simulation = aligning(Neighbourhood::Topological, 7);
+    simulation.birds.push(Bird::new(Point2::new(10.0, 10.0), Vector2::new(1.0, 0.0)));
+    simulation.birds.push(Bird::new(Point2::new(790.0, 590.0), Vector2::new(0.0, 1.0)));
+
+    simulation.step(1.0 / 60.0);
+
+    assert!(simulation.birds[0].vel.y > 0.0);
+    assert!(simulation.birds[1].vel.x > 0.0);
+}
+
+#[test]
+fn test_neighbourhood_config_and_options() {
+    let config = Config::parse("[parameters]\nneighbourhood = \"topological\"\ntopological_neighbours = 6").unwrap();
+    assert_eq!(config.parameters.neighbourhood, Neighbourhood::Topological);
+    assert_eq!(config.parameters.topological_neighbours, 6);
+
+    let error = Config::parse("[parameters]\ntopological_neighbours = 0").unwrap_err();
+    assert!(error.to_string().contains("parameters.topological_neighbours"), "{}", error);
+
+    let options = Options::from_iter_safe(vec!["boids", "--neighbourhood", "topological", "--neighbours", "3"]).unwrap();
+    assert_eq!(options.neighbourhood, Some(Neighbourhood::Topological));
+    assert_eq!(options.neighbours, Some(3));
+    assert!(Options::from_iter_safe(vec!["boids", "--neighbourhood", "nearest"]).is_err());
+}
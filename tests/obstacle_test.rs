@@ -0,0 +1,88 @@
+use boids::entities::{ Motion, Obstacle };
+use boids::simulation::Simulation;
+use ggez::nalgebra::{ distance, Point2, Vector2 };
+
+#[test]
+fn test_static_obstacle_stays() {
+    let mut obstacle = Obstacle::new(Point2::new(100.0, 100.0), Some(50.0));
+
+    obstacle.update(800.0, 600.0, Some(Point2::new(0.0, 0.0)));
+
+    assert_eq!(obstacle.pos, Point2::new(100.0, 100.0));
+    assert_eq!(obstacle.vel, Vector2::new(0.0, 0.0));
+}
+
+#[test]
+fn test_drift_bounces_off_edges() {
+    let mut obstacle = Obstacle::with_motion(Point2::new(795.0, 300.0), Some(50.0), Motion::Drift { vel: Vector2::new(2.0, 0.0) });
+
+    for _ in 0..10 {
+        obstacle.update(800.0, 600.0, None);
+    }
+
+    assert!(obstacle.pos.x < 800.0);
+    assert_eq!(obstacle.motion, Motion::Drift { vel: Vector2::new(-2.0, 0.0) });
+}
+
+#[test]
+fn test_orbit_keeps_radius() {
+    let center = Point2::new(400.0, 300.0);
+    let motion = Motion::Orbit { center, radius: 80.0, angular_speed: 0.1, angle: 0.0 };
+    let mut obstacle = Obstacle::with_motion(Point2::new(480.0, 300.0), Some(50.0), motion);
+
+    for _ in 0..20 {
+        obstacle.update(800.0, 600.0, None);
+        assert!((distance(&obstacle.pos, &center) - 80.0).abs() < 1e-3);
+    }
+    assert!(obstacle.pos.y > 300.0);
+}
+
+#[test]
+fn test_path_loops_through_waypoints() {
+    let waypoints = vec![Point2::new(110.0, 100.0), Point2::new(100.0, 100.0)];
+    let mut obstacle = Obstacle::with_motion(Point2::new(100.0, 100.0), Some(50.0), Motion::Path { waypoints, speed: 2.0, next: 0 });
+
+    for _ in 0..5 {
+        obstacle.update(800.0, 600.0, None);
+    }
+    assert_eq!(obstacle.pos, Point2::new(110.0, 100.0));
+
+    for _ in 0..5 {
+        obstacle.update(800.0, 600.0, None);
+    }
+    assert_eq!(obstacle.pos, Point2::new(100.0, 100.0));
+
+    obstacle.update(800.0, 600.0, None);
+    assert_eq!(obstacle.pos, Point2::new(102.0, 100.0));
+}
+
+#[test]
+fn test_simulation_moves_obstacles_towards_mouse() {
+    let mut simulation = Simulation::with_seed(800.0, 600.0, 1);
+    simulation.spawn_moving_obstacle(Point2::new(100.0, 100.0), Motion::FollowMouse { speed: 3.0 });
+
+    simulation.step(1.0 / 60.0);
+    assert_eq!(simulation.obstacles[0].pos, Point2::new(100.0, 100.0));
+
+    simulation.mouse = Some(Point2::new(200.0, 100.0));
+    simulation.step(1.0 / 60.0);
+    assert_eq!(simulation.obstacles[0].pos, Point2::new(103.0, 100.0));
+    assert_eq!(simulation.obstacles[0].vel, Vector2::new(3.0, 0.0));
+}
+
+#[test]
+fn test_drifting_obstacle_direction_follows_the_seed() {
+    let drift = |seed: u64| {
+        let mut simulation = Simulation::with_seed(800.0, 600.0, seed);
+        simulation.spawn_drifting_obstacle(Point2::new(400.0, 300.0), 1.5);
+        match simulation.obstacles[0].motion {
+            Motion::Drift { vel } => vel,
+            ref motion => panic!("unexpected motion {:?}", motion),
+        }
+    };
+
+    let vel = drift(7);
+    assert!((vel.norm() - 1.5).abs() < 1e-5);
+    assert_eq!(vel, drift(7));
+    assert_ne!(vel, drift(8));
+}
@@ -0,0 +1,156 @@
+use boids::config::Config;
+use boids::panel::{ ParameterPanel, SLIDERS };
+use boids::simulation::{ Parameters, Simulation };
+use ggez::nalgebra::Point2;
+
+fn slider_index(label: &str) -> usize {
+    SLIDERS.iter().position(|slider| slider.label == label).unwrap()
+}
+
+/// The point on the track of `row` at `fraction` of its length.
+fn track_point(panel: &ParameterPanel, row: usize, fraction: f32) -> Point2<f32> {
+    Point2::new(
+        panel.pos.x + ParameterPanel::PADDING + ParameterPanel::LABEL_WIDTH + fraction * ParameterPanel::TRACK_WIDTH,
+        panel.pos.y + ParameterPanel::PADDING + (row as f32 + 0.5) * ParameterPanel::ROW_HEIGHT)
+}
+
+fn simulation() -> Simulation {
+    Simulation::with_seed(800.0, 600.0, 1)
+}
+
+fn visible_panel() -> ParameterPanel {
+    let mut panel = ParameterPanel::new(Point2::new(400.0, 0.0));
+    panel.visible = true;
+    panel
+}
+
+#[test]
+fn test_slider_clamps_and_rounds_to_step() {
+    let slider = &SLIDERS[slider_index("Cohesion distance")];
+    let mut parameters = Parameters::default();
+    slider.set_value(&mut parameters, 123.0);
+    assert_eq!(parameters.cohesion_view_distance, 125.0);
+    slider.set_value(&mut parameters, 1000.0);
+    assert_eq!(parameters.cohesion_view_distance, slider.max);
+    slider.set_value(&mut parameters, -3.0);
+    assert_eq!(parameters.cohesion_view_distance, slider.min);
+    assert_eq!(slider.fraction(&parameters), 0.0);
+    assert_eq!(slider.format(&parameters), "5");
+
+    let slider = &SLIDERS[slider_index("Max steering")];
+    slider.set_value(&mut parameters, 0.123);
+    assert!((parameters.max_steering_velocity - 0.12).abs() < 1e-6);
+    assert_eq!(slider.format(&parameters), "0.12");
+}
+
+#[test]
+fn test_every_slider_value_is_a_valid_config() {
+    for slider in SLIDERS.iter() {
+        for &value in [slider.min, slider.max].iter() {
+            let mut config = Config::default();
+            slider.set_value(&mut config.parameters, value);
+            assert!(config.validate().is_ok(), "{} = {} is rejected", slider.label, value);
+        }
+    }
+}
+
+#[test]
+fn test_every_default_is_in_range() {
+    let parameters = Parameters::default();
+    for slider in SLIDERS.iter() {
+        let value = slider.value(&parameters);
+        assert!(value >= slider.min && value <= slider.max, "{} = {} is out of range", slider.label, value);
+    }
+    for &label in ["Look-ahead distance", "Boundary margin", "Predator view distance", "Predator kill radius"].iter() {
+        assert!(SLIDERS.iter().any(|slider| slider.label == label), "no {} slider", label);
+    }
+}
+
+#[test]
+fn test_dragging_a_track_sets_the_value() {
+    let mut panel = visible_panel();
+    let mut simulation = simulation();
+    let row = slider_index("Alignment weight");
+
+    assert!(panel.mouse_down(track_point(&panel, row, 0.5), &mut simulation));
+    assert!(panel.is_dragging());
+    assert_eq!(simulation.parameters.alignment_modifier, 2.5);
+
+    // keeps following the mouse outside the track, clamped to the range
+    panel.mouse_motion(Point2::new(0.0, 500.0), &mut simulation);
+    assert_eq!(simulation.parameters.alignment_modifier, 0.0);
+    assert!(panel.mouse_up());
+    assert!(!panel.is_dragging());
+
+    panel.mouse_motion(track_point(&panel, row, 1.0), &mut simulation);
+    assert_eq!(simulation.parameters.alignment_modifier, 0.0);
+    assert!(!panel.mouse_up());
+}
+
+#[test]
+fn test_clicks_outside_the_tracks() {
+    let mut panel = visible_panel();
+    let mut simulation = simulation();
+
+    // on a label: taken by the panel but changes nothing
+    let label = Point2::new(panel.pos.x + 20.0, track_point(&panel, 0, 0.0).y);
+    assert!(panel.mouse_down(label, &mut simulation));
+    assert!(!panel.is_dragging());
+    assert_eq!(simulation.parameters, Parameters::default());
+
+    // beside the panel: left to the world
+    assert!(!panel.mouse_down(Point2::new(10.0, 10.0), &mut simulation));
+
+    // a hidden panel takes nothing
+    panel.visible = false;
+    assert!(!panel.contains(track_point(&panel, 0, 0.5)));
+    assert!(!panel.mouse_down(track_point(&panel, 0, 0.5), &mut simulation));
+    assert_eq!(simulation.parameters, Parameters::default());
+}
+
+#[test]
+fn test_scrolling_changes_one_step() {
+    let mut panel = visible_panel();
+    let mut simulation = simulation();
+    let row = slider_index("Separation distance");
+    let point = Point2::new(panel.pos.x + 20.0, track_point(&panel, row, 0.0).y);
+
+    assert_eq!(panel.row_at(point), Some(row));
+    assert!(panel.scroll(point, 1.0, &mut simulation));
+    assert_eq!(simulation.parameters.separation_view_distance, Parameters::SEPARATION_VIEW_DISTANCE + 5.0);
+    assert!(panel.scroll(point, -2.0, &mut simulation));
+    assert_eq!(simulation.parameters.separation_view_distance, Parameters::SEPARATION_VIEW_DISTANCE - 5.0);
+    assert!(!panel.scroll(Point2::new(0.0, 0.0), 1.0, &mut simulation));
+}
+
+#[test]
+fn test_sliders_change_species_parameters_too() {
+    let config = Config::parse(r#"
+        [[species]]
+        name = "starling"
+        [species.parameters]
+        max_speed = 3.0
+
+        [[species]]
+        name = "crow"
+        [species.parameters]
+        max_speed = 5.0
+        cohesion_view_distance = 40.0
+    "#).unwrap();
+    let mut simulation = simulation();
+    simulation.set_species(config.species());
+    let mut panel = visible_panel();
+
+    // only the crow sets its own cohesion distance
+    assert!(panel.mouse_down(track_point(&panel, slider_index("Cohesion distance"), 0.0), &mut simulation));
+    assert_eq!(simulation.parameters.cohesion_view_distance, 5.0);
+    assert_eq!(simulation.species[0].parameters.unwrap().cohesion_view_distance, 5.0);
+    assert_eq!(simulation.species[1].parameters.unwrap().cohesion_view_distance, 40.0);
+    panel.mouse_up();
+
+    // both set their own max speed, which survives
+    assert!(panel.mouse_down(track_point(&panel, slider_index("Max speed"), 0.0), &mut simulation));
+    assert_eq!(simulation.parameters.max_speed, 0.5);
+    assert_eq!(simulation.species[0].parameters.unwrap().max_speed, 3.0);
+    assert_eq!(simulation.species[1].parameters.unwrap().max_speed, 5.0);
+}
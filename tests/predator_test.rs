@@ -0,0 +1,78 @@
+use boids::entities::Bird;
+use boids::simulation::{ PredatorTarget, Simulation, UpdateMode };
+use ggez::nalgebra::{ Point2, Vector2 };
+
+fn simulation() -> Simulation {
+    let mut simulation = Simulation::with_seed(800.0, 600.0, 1);
+    simulation.set_rule_enabled("random_movement", false);
+    simulation
+}
+
+#[test]
+fn test_predator_chases_nearest_bird() {
+    let mut simulation = simulation();
+    simulation.birds.push(Bird::new(Point2::new(500.0, 300.0), Vector2::new(0.0, 0.0)));
+    simulation.birds.push(Bird::new(Point2::new(100.0, 300.0), Vector2::new(0.0, 0.0)));
+    simulation.spawn_predator(Point2::new(400.0, 300.0));
+
+    simulation.step(1.0 / 60.0);
+
+    assert!(simulation.predators[0].vel.x > 0.0);
+}
+
+#[test]
+fn test_predator_chases_densest_group() {
+    let mut simulation = simulation();
+    simulation.parameters.predator_target = PredatorTarget::Densest;
+    simulation.birds.push(Bird::new(Point2::new(450.0, 300.0), Vector2::new(0.0, 0.0)));
+    for i in 0..5 {
+        simulation.birds.push(Bird::new(Point2::new(250.0 + i as f32 * 5.0, 300.0), Vector2::new(0.0, 0.0)));
+    }
+    simulation.spawn_predator(Point2::new(400.0, 300.0));
+
+    simulation.step(1.0 / 60.0);
+
+    assert!(simulation.predators[0].vel.x < 0.0);
+}
+
+#[test]
+fn test_birds_flee_from_predator() {
+    let mut simulation = simulation();
+    simulation.birds.push(Bird::new(Point2::new(450.0, 300.0), Vector2::new(0.0, 0.0)));
+    simulation.spawn_predator(Point2::new(400.0, 300.0));
+
+    simulation.step(1.0 / 60.0);
+
+    assert!(simulation.birds[0].vel.x > 0.0);
+}
+
+#[test]
+fn test_predator_kills_bird_on_contact() {
+    let mut simulation = simulation();
+    simulation.birds.push(Bird::new(Point2::new(402.0, 300.0), Vector2::new(0.0, 0.0)));
+    simulation.birds.push(Bird::new(Point2::new(700.0, 300.0), Vector2::new(0.0, 0.0)));
+    simulation.spawn_predator(Point2::new(400.0, 300.0));
+
+    simulation.step(1.0 / 60.0);
+
+    assert_eq!(simulation.birds.len(), 1);
+    assert_eq!(simulation.birds[0].pos.x, 700.0);
+}
+
+#[test]
+fn test_caught_birds_are_not_seen_in_the_same_tick() {
+    for &update_mode in [UpdateMode::Sequential, UpdateMode::DoubleBuffered].iter() {
+        let mut simulation = simulation();
+        simulation.update_mode = update_mode;
+        simulation.set_rule_enabled("flee", false);
+        simulation.birds.push(Bird::new(Point2::new(402.0, 300.0), Vector2::new(0.0, -2.0)));
+        simulation.birds.push(Bird::new(Point2::new(440.0, 300.0), Vector2::new(0.0, 0.0)));
+        simulation.spawn_predator(Point2::new(400.0, 300.0));
+
+        simulation.step(1.0 / 60.0);
+
+        // neither aligned with nor drawn towards the bird that was caught
+        assert_eq!(simulation.birds.len(), 1);
+        assert_eq!(simulation.birds[0].vel, Vector2::new(0.0, 0.0));
+    }
+}
@@ -0,0 +1,185 @@
+use boids::cli::Options;
+use boids::entities::{ Motion, Shape };
+use boids::scene::{ Region, Scene, SceneError };
+use boids::simulation::Simulation;
+use boids::species::Species;
+use boids::tools::Tools;
+use ggez::nalgebra::Point2;
+use structopt::StructOpt;
+
+const SCENE: &str = r#"
+[world]
+width = 1600.0
+height = 900.0
+
+[[obstacles]]
+pos = [400.0, 300.0]
+
+[[obstacles]]
+pos = [800.0, 450.0]
+radius = 30.0
+shape = { type = "rect", half_width = 100.0, half_height = 20.0 }
+motion = { type = "orbit", center = [800.0, 450.0], radius = 150.0, angular_speed = 0.01 }
+
+[[spawn]]
+count = 50
+species = "sparrow"
+speed = [1.0, 2.0]
+heading = [0.0, 90.0]
+region = { type = "rect", min = [100.0, 100.0], max = [300.0, 200.0] }
+
+[[spawn]]
+count = 30
+region = { type = "circle", center = [1200.0, 600.0], radius = 80.0 }
+"#;
+
+fn two_species() -> Simulation {
+    let mut simulation = Simulation::with_seed(800.0, 600.0, 5);
+    simulation.set_species(vec![Species::new("starling"), Species::new("sparrow")]);
+    simulation
+}
+
+#[test]
+fn test_scene_sets_world_obstacles_and_birds() {
+    let scene = Scene::parse(SCENE).unwrap();
+    let mut simulation = two_species();
+    scene.apply(&mut simulation).unwrap();
+
+    assert_eq!((simulation.width, simulation.height), (1600.0, 900.0));
+    assert_eq!(simulation.obstacles.len(), 2);
+    assert_eq!(simulation.obstacles[0].radius, None);
+    assert_eq!(simulation.obstacles[0].shape, Shape::Point);
+    assert_eq!(simulation.obstacles[1].radius, Some(30.0));
+    assert_eq!(simulation.obstacles[1].shape, Shape::Rect { half_width: 100.0, half_height: 20.0 });
+    assert_eq!(simulation.obstacles[1].motion, Motion::Orbit { center: Point2::new(800.0, 450.0), radius: 150.0, angular_speed: 0.01, angle: 0.0 });
+    assert_eq!(simulation.birds.len(), 80);
+
+    for bird in simulation.birds[..50].iter() {
+        assert_eq!(bird.species, 1);
+        assert!(bird.pos.x >= 100.0 && bird.pos.x <= 300.0 && bird.pos.y >= 100.0 && bird.pos.y <= 200.0);
+        let speed = Tools::vector_length(&bird.vel);
+        assert!((1.0 - 1e-4 ..= 2.0 + 1e-4).contains(&speed));
+        assert!(bird.vel.x >= -1e-4 && bird.vel.y >= -1e-4);
+    }
+    for (i, bird) in simulation.birds[50..].iter().enumerate() {
+        assert_eq!(bird.species, i % 2);
+        assert!(simulation.distance(bird.pos, Point2::new(1200.0, 600.0)) <= 80.0 + 1e-3);
+    }
+}
+
+#[test]
+fn test_saved_layout_loads_back() {
+    let mut simulation = two_species();
+    Scene::parse(SCENE).unwrap().apply(&mut simulation).unwrap();
+    let (pos, shape) = Shape::polygon(&[Point2::new(10.0, 10.0), Point2::new(60.0, 10.0), Point2::new(30.0, 50.0)]);
+    simulation.spawn_shaped_obstacle(pos, shape);
+    simulation.spawn_moving_obstacle(Point2::new(50.0, 50.0), Motion::Path { waypoints: vec![Point2::new(50.0, 50.0), Point2::new(90.0, 50.0)], speed: 1.0, next: 0 });
+
+    let scene = Scene::from_simulation(&simulation);
+    let loaded = Scene::parse(&scene.to_toml().unwrap()).unwrap();
+    assert_eq!(loaded, scene);
+    assert!(loaded.spawn.is_empty());
+
+    let mut copy = Simulation::with_seed(100.0, 100.0, 1);
+    loaded.apply(&mut copy).unwrap();
+    assert_eq!((copy.width, copy.height), (1600.0, 900.0));
+    assert_eq!(copy.obstacles.len(), 4);
+    for (a, b) in copy.obstacles.iter().zip(simulation.obstacles.iter()) {
+        assert_eq!(a.pos, b.pos);
+        assert_eq!(a.radius, b.radius);
+        assert_eq!(a.shape, b.shape);
+        assert_eq!(a.motion, b.motion);
+    }
+}
+
+#[test]
+fn test_same_seed_spawns_same_birds() {
+    let scene = Scene::parse(SCENE).unwrap();
+    let mut a = two_species();
+    let mut b = two_species();
+    scene.apply(&mut a).unwrap();
+    scene.apply(&mut b).unwrap();
+    for (a, b) in a.birds.iter().zip(b.birds.iter()) {
+        assert_eq!(a.pos, b.pos);
+        assert_eq!(a.vel, b.vel);
+    }
+}
+
+#[test]
+fn test_invalid_scene_names_the_key() {
+    let invalid = |text: &str| match Scene::parse(text) {
+        Err(SceneError::Invalid { key, .. }) => key,
+        other => panic!("expected an invalid value, got {:?}", other)
+    };
+    assert_eq!(invalid("[world]\nwidth = 0.0\nheight = 10.0"), "world.width");
+    assert_eq!(invalid("[[obstacles]]\npos = [0.0, 0.0]\n[[obstacles]]\npos = [0.0, 0.0]\nradius = -1.0"), "obstacles[1].radius");
+    assert_eq!(invalid("[[obstacles]]\npos = [0.0, 0.0]\nshape = { type = \"polygon\", vertices = [[0.0, 0.0], [1.0, 0.0]] }"), "obstacles[0].shape");
+    assert_eq!(invalid("[[obstacles]]\npos = [0.0, 0.0]\nshape = { type = \"polygon\", vertices = [[0.0, 0.0], [1.0, 1.0], [3.0, 3.0]] }"), "obstacles[0].shape");
+    assert_eq!(invalid("[[spawn]]\ncount = 1\nspeed = [2.0, 1.0]\nregion = { type = \"circle\", center = [0.0, 0.0], radius = 1.0 }"), "spawn[0].speed");
+    assert_eq!(invalid("[[spawn]]\ncount = 1\nregion = { type = \"rect\", min = [5.0, 0.0], max = [0.0, 5.0] }"), "spawn[0].region");
+    assert_eq!(invalid("[[obstacles]]\npos = [0.0, 0.0]\nshape = { type = \"rect\", half_width = 0.0, half_height = 5.0 }"), "obstacles[0].shape.half_width");
+    assert_eq!(invalid("[[obstacles]]\npos = [0.0, 0.0]\nshape = { type = \"rect\", half_width = 5.0, half_height = -5.0 }"), "obstacles[0].shape.half_height");
+    assert_eq!(invalid("[[obstacles]]\npos = [0.0, 0.0]\nshape = { type = \"wall\", start = [3.0, 4.0], end = [3.0, 4.0] }"), "obstacles[0].shape");
+    assert_eq!(invalid("[[obstacles]]\npos = [0.0, 0.0]\nmotion = { type = \"orbit\", center = [0.0, 0.0], radius = 0.0, angular_speed = 0.01 }"), "obstacles[0].motion.radius");
+    assert_eq!(invalid("[[obstacles]]\npos = [0.0, 0.0]\nmotion = { type = \"path\", waypoints = [[0.0, 0.0]], speed = -1.0 }"), "obstacles[0].motion.speed");
+    assert_eq!(invalid("[[obstacles]]\npos = [0.0, 0.0]\nmotion = { type = \"follow_mouse\", speed = -1.0 }"), "obstacles[0].motion.speed");
+    assert_eq!(invalid("[[obstacles]]\npos = [nan, 0.0]"), "obstacles[0].pos");
+    assert_eq!(invalid("[[spawn]]\ncount = 1\nregion = { type = \"rect\", min = [0.0, 0.0], max = [inf, 5.0] }"), "spawn[0].region");
+    assert_eq!(invalid("[[spawn]]\ncount = 1\nregion = { type = \"circle\", center = [nan, 0.0], radius = 1.0 }"), "spawn[0].region");
+    assert!(matches!(Scene::parse("[[spawn]]\ncount = 1\nregion = { type = \"square\" }"), Err(SceneError::Parse(_))));
+    assert!(matches!(Scene::parse("gravity = 1.0"), Err(SceneError::Parse(_))));
+
+    let scene = Scene::parse("[[spawn]]\ncount = 3\nspecies = \"crow\"\nregion = { type = \"circle\", center = [0.0, 0.0], radius = 1.0 }").unwrap();
+    let mut simulation = two_species();
+    match scene.apply(&mut simulation) {
+        Err(SceneError::Invalid { key, .. }) => assert_eq!(key, "spawn[0].species"),
+        other => panic!("expected an unknown species, got {:?}", other)
+    }
+    assert!(simulation.birds.is_empty());
+}
+
+#[test]
+fn test_empty_ranges_and_regions_are_allowed() {
+    let scene = Scene::parse("[[spawn]]\ncount = 5\nspeed = [1.5, 1.5]\nheading = [90.0, 90.0]\nregion = { type = \"rect\", min = [10.0, 20.0], max = [10.0, 20.0] }").unwrap();
+    assert_eq!(scene.spawn[0].region, Region::Rect { min: Point2::new(10.0, 20.0), max: Point2::new(10.0, 20.0) });
+    let mut simulation = Simulation::with_seed(800.0, 600.0, 3);
+    scene.apply(&mut simulation).unwrap();
+    for bird in simulation.birds.iter() {
+        assert_eq!(bird.pos, Point2::new(10.0, 20.0));
+        assert!(bird.vel.x.abs() < 1e-5 && (bird.vel.y - 1.5).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn test_scene_option() {
+    let options = Options::from_iter(&["boids", "--scene", "arena.toml"]);
+    assert_eq!(options.scene_path().to_str(), Some("arena.toml"));
+    let options = Options::from_iter(&["boids"]);
+    assert_eq!(options.scene_path().to_str(), Some(Scene::DEFAULT_PATH));
+    assert!(options.load_scene().unwrap().is_none());
+    let options = Options::from_iter(&["boids", "--scene", "missing.toml"]);
+    assert!(matches!(options.load_scene(), Err(SceneError::Io(..))));
+}
+
+#[test]
+fn test_flock_is_spawned_in_the_scene_world() {
+    let scene = Scene::parse("[world]\nwidth = 600.0\nheight = 400.0").unwrap();
+    for &spawn in ["random", "grid", "circle"].iter() {
+        let options = Options::from_iter(&["boids", "--birds", "40", "--spawn", spawn, "--seed", "2"]);
+        let config = options.load_config().unwrap();
+        let mut simulation = options.initial_simulation(&config, scene.world).unwrap();
+        scene.apply(&mut simulation).unwrap();
+
+        assert_eq!((simulation.width, simulation.height), (600.0, 400.0));
+        assert_eq!(simulation.birds.len(), 40);
+        for bird in simulation.birds.iter() {
+            assert!(bird.pos.x >= 0.0 && bird.pos.x <= 600.0, "{} {:?}", spawn, bird.pos);
+            assert!(bird.pos.y >= 0.0 && bird.pos.y <= 400.0, "{} {:?}", spawn, bird.pos);
+        }
+    }
+
+    // the lone default bird starts in the middle of the scene's world
+    let options = Options::from_iter(&["boids", "--seed", "2"]);
+    let simulation = options.simulation(&options.load_config().unwrap(), scene.world);
+    assert_eq!(simulation.birds[0].pos, Point2::new(300.0, 200.0));
+}
@@ -0,0 +1,84 @@
+use boids::entities::{ Bird, Shape };
+use boids::simulation::Simulation;
+use boids::tools::Tools;
+use ggez::nalgebra::{ Point2, Vector2 };
+
+#[test]
+fn test_closest_point_on_segment() {
+    let start = Point2::new(0.0, 0.0);
+    let end = Point2::new(10.0, 0.0);
+
+    assert_eq!(Tools::closest_point_on_segment(start, end, Point2::new(4.0, 3.0)), Point2::new(4.0, 0.0));
+    assert_eq!(Tools::closest_point_on_segment(start, end, Point2::new(-5.0, 3.0)), start);
+    assert_eq!(Tools::closest_point_on_segment(start, end, Point2::new(15.0, -3.0)), end);
+    assert_eq!(Tools::closest_point_on_segment(start, start, Point2::new(1.0, 1.0)), start);
+}
+
+#[test]
+fn test_rect_closest_point_and_inside() {
+    let (pos, rect) = Shape::rect(Point2::new(100.0, 100.0), Point2::new(200.0, 150.0));
+
+    assert_eq!(pos, Point2::new(150.0, 125.0));
+    assert_eq!(rect.closest_point(pos, Point2::new(150.0, 50.0)), Point2::new(150.0, 100.0));
+    assert_eq!(rect.closest_point(pos, Point2::new(250.0, 200.0)), Point2::new(200.0, 150.0));
+    // from the inside the closest point is on the nearest edge
+    assert_eq!(rect.closest_point(pos, Point2::new(190.0, 125.0)), Point2::new(200.0, 125.0));
+    assert!(rect.contains(pos, Point2::new(190.0, 125.0)));
+    assert!(!rect.contains(pos, Point2::new(210.0, 125.0)));
+}
+
+#[test]
+fn test_concave_polygon_contains() {
+    // an L shape with the top right quarter missing
+    let (pos, polygon) = Shape::polygon(&[
+        Point2::new(0.0, 0.0), Point2::new(10.0, 0.0), Point2::new(10.0, 20.0),
+        Point2::new(20.0, 20.0), Point2::new(20.0, 30.0), Point2::new(0.0, 30.0)
+    ]);
+
+    assert!(polygon.contains(pos, Point2::new(5.0, 5.0)));
+    assert!(polygon.contains(pos, Point2::new(15.0, 25.0)));
+    assert!(!polygon.contains(pos, Point2::new(15.0, 5.0)));
+    assert_eq!(polygon.closest_point(pos, Point2::new(15.0, 10.0)), Point2::new(10.0, 10.0));
+}
+
+#[test]
+fn test_walls_have_no_inside() {
+    let (pos, wall) = Shape::wall(Point2::new(0.0, 0.0), Point2::new(10.0, 0.0));
+
+    assert_eq!(pos, Point2::new(5.0, 0.0));
+    assert!(!wall.contains(pos, Point2::new(5.0, 0.0)));
+    assert_eq!(wall.vertices(pos), vec![Point2::new(0.0, 0.0), Point2::new(10.0, 0.0)]);
+}
+
+fn evasion_only() -> Simulation {
+    let mut simulation = Simulation::with_seed(800.0, 600.0, 1);
+    for name in &["alignment", "separation", "cohesion", "random_movement", "flee"] {
+        simulation.set_rule_enabled(name, false);
+    }
+    simulation
+}
+
+#[test]
+fn test_birds_steer_away_from_wall() {
+    let mut simulation = evasion_only();
+    let (pos, wall) = Shape::wall(Point2::new(100.0, 300.0), Point2::new(700.0, 300.0));
+    simulation.spawn_shaped_obstacle(pos, wall);
+    // far from the wall's centre, so only closest-point avoidance notices it
+    simulation.birds.push(Bird::new(Point2::new(150.0, 280.0), Vector2::new(1.0, 0.0)));
+
+    simulation.step(1.0 / 60.0);
+
+    assert!(simulation.birds[0].vel.y < 0.0);
+}
+
+#[test]
+fn test_birds_inside_a_rect_steer_out() {
+    let mut simulation = evasion_only();
+    let (pos, rect) = Shape::rect(Point2::new(100.0, 100.0), Point2::new(500.0, 500.0));
+    simulation.spawn_shaped_obstacle(pos, rect);
+    simulation.birds.push(Bird::new(Point2::new(490.0, 300.0), Vector2::new(0.0, 0.0)));
+
+    simulation.step(1.0 / 60.0);
+
+    assert!(simulation.birds[0].vel.x > 0.0);
+}
@@ -0,0 +1,225 @@
+use boids::entities::Bird;
+use boids::rules::{ Rules, SteeringRule, World };
+use boids::simulation::{ NeighbourSearch, Simulation, UpdateMode };
+use ggez::nalgebra::{ Point2, Vector2 };
+use rand::RngCore;
+
+#[test]
+fn test_step_moves_birds() {
+    let mut simulation = Simulation::new(800.0, 600.0);
+    simulation.birds.push(Bird::new(Point2::new(400.0, 300.0), Vector2::new(1.0, 0.0)));
+    simulation.set_rule_enabled("random_movement", false);
+
+    simulation.step(1.0 / 60.0);
+
+    assert_eq!(simulation.tick, 1);
+    assert!(simulation.birds[0].pos.x > 400.0);
+    assert_eq!(simulation.birds[0].pos.y, 300.0);
+}
+
+#[test]
+fn test_dead_entities_are_removed() {
+    let mut simulation = Simulation::new(800.0, 600.0);
+    simulation.spawn_bird(Point2::new(100.0, 100.0));
+    simulation.spawn_bird(Point2::new(200.0, 200.0));
+    simulation.spawn_obstacle(Point2::new(300.0, 300.0));
+
+    simulation.kill_birds();
+    simulation.kill_obstacles();
+    simulation.step(1.0 / 60.0);
+
+    assert!(simulation.birds.is_empty());
+    assert!(simulation.obstacles.is_empty());
+}
+
+#[test]
+fn test_separation_pushes_birds_apart() {
+    let mut simulation = Simulation::new(800.0, 600.0);
+    simulation.set_rule_enabled("alignment", false);
+    simulation.set_rule_enabled("cohesion", false);
+    simulation.set_rule_enabled("random_movement", false);
+    simulation.birds.push(Bird::new(Point2::new(400.0, 300.0), Vector2::new(0.0, 0.0)));
+    simulation.birds.push(Bird::new(Point2::new(410.0, 300.0), Vector2::new(0.0, 0.0)));
+
+    simulation.step(1.0 / 60.0);
+
+    assert!(simulation.birds[0].vel.x < 0.0);
+    assert!(simulation.birds[1].vel.x > 0.0);
+}
+
+fn assert_grid_matches_brute_force(update_mode: UpdateMode) {
+    let mut grid = Simulation::new(800.0, 600.0);
+    grid.set_rule_enabled("random_movement", false);
+    grid.update_mode = update_mode;
+    for i in 0..200 {
+        let x = (i * 37 % 800) as f32;
+        let y = (i * 91 % 600) as f32;
+        let vel = Vector2::new(((i % 7) as f32 - 3.0) * 0.5, ((i % 5) as f32 - 2.0) * 0.5);
+        grid.birds.push(Bird::new(Point2::new(x, y), vel));
+    }
+    let mut brute_force = Simulation::new(800.0, 600.0);
+    brute_force.set_rule_enabled("random_movement", false);
+    brute_force.update_mode = update_mode;
+    brute_force.neighbour_search = NeighbourSearch::BruteForce;
+    brute_force.birds = grid.birds.clone();
+
+    for _ in 0..50 {
+        grid.step(1.0 / 60.0);
+        brute_force.step(1.0 / 60.0);
+    }
+
+    for (a, b) in grid.birds.iter().zip(brute_force.birds.iter()) {
+        assert_eq!(a.pos, b.pos);
+        assert_eq!(a.vel, b.vel);
+    }
+}
+
+#[test]
+fn test_grid_matches_brute_force_sequential() {
+    assert_grid_matches_brute_force(UpdateMode::Sequential);
+}
+
+#[test]
+fn test_grid_matches_brute_force_double_buffered() {
+    assert_grid_matches_brute_force(UpdateMode::DoubleBuffered);
+}
+
+#[test]
+fn test_double_buffered_reads_previous_tick() {
+    let mut simulation = Simulation::new(800.0, 600.0);
+    simulation.update_mode = UpdateMode::DoubleBuffered;
+    simulation.set_rule_enabled("separation", false);
+    simulation.set_rule_enabled("cohesion", false);
+    simulation.set_rule_enabled("random_movement", false);
+    simulation.birds.push(Bird::new(Point2::new(400.0, 300.0), Vector2::new(1.0, 0.0)));
+    simulation.birds.push(Bird::new(Point2::new(450.0, 300.0), Vector2::new(-1.0, 0.0)));
+
+    simulation.step(1.0 / 60.0);
+
+    // both birds aligned towards the other's old heading, so they mirror each other
+    assert_eq!(simulation.birds[0].vel.x, -simulation.birds[1].vel.x);
+}
+
+fn seeded_run(seed: u64, update_mode: UpdateMode) -> Vec<Bird> {
+    let mut simulation = Simulation::with_seed(800.0, 600.0, seed);
+    simulation.update_mode = update_mode;
+    for i in 0..40 {
+        simulation.spawn_bird(Point2::new(300.0 + (i % 8) as f32 * 20.0, 200.0 + (i / 8) as f32 * 20.0));
+    }
+    for tick in 0..60 {
+        if tick == 30 {
+            simulation.spawn_bird(Point2::new(100.0, 100.0));
+        }
+        simulation.step(1.0 / 60.0);
+    }
+    simulation.birds
+}
+
+#[test]
+fn test_same_seed_gives_identical_trajectories() {
+    for &update_mode in [UpdateMode::Sequential, UpdateMode::DoubleBuffered].iter() {
+        let first = seeded_run(42, update_mode);
+        let second = seeded_run(42, update_mode);
+
+        assert_eq!(first.len(), second.len());
+        for (a, b) in first.iter().zip(second.iter()) {
+            assert_eq!(a.pos.x.to_bits(), b.pos.x.to_bits());
+            assert_eq!(a.pos.y.to_bits(), b.pos.y.to_bits());
+            assert_eq!(a.vel.x.to_bits(), b.vel.x.to_bits());
+            assert_eq!(a.vel.y.to_bits(), b.vel.y.to_bits());
+        }
+    }
+}
+
+#[test]
+fn test_different_seeds_diverge() {
+    let first = seeded_run(1, UpdateMode::DoubleBuffered);
+    let second = seeded_run(2, UpdateMode::DoubleBuffered);
+
+    assert!(first.iter().zip(second.iter()).any(|(a, b)| a.pos != b.pos));
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn test_parallel_matches_serial() {
+    let run = |parallel: bool| {
+        let mut simulation = Simulation::with_seed(800.0, 600.0, 7);
+        simulation.update_mode = UpdateMode::DoubleBuffered;
+        simulation.parallel = parallel;
+        for i in 0..200 {
+            simulation.spawn_bird(Point2::new((i * 37 % 800) as f32, (i * 91 % 600) as f32));
+        }
+        for _ in 0..50 {
+            simulation.step(1.0 / 60.0);
+        }
+        simulation.birds
+    };
+    let parallel = run(true);
+    let serial = run(false);
+
+    assert_eq!(parallel.len(), serial.len());
+    for (a, b) in parallel.iter().zip(serial.iter()) {
+        assert_eq!(a.pos.x.to_bits(), b.pos.x.to_bits());
+        assert_eq!(a.pos.y.to_bits(), b.pos.y.to_bits());
+        assert_eq!(a.vel.x.to_bits(), b.vel.x.to_bits());
+        assert_eq!(a.vel.y.to_bits(), b.vel.y.to_bits());
+    }
+}
+
+struct Wind;
+
+impl SteeringRule for Wind {
+    fn name(&self) -> &str {
+        "wind"
+    }
+
+    fn color(&self) -> (u8, u8, u8) {
+        (255, 255, 255)
+    }
+
+    fn steer(&self, _bird: &Bird, _neighbours: &[usize], _world: &World, _rng: &mut dyn RngCore) -> Vector2<f32> {
+        Vector2::new(0.5, 0.0)
+    }
+}
+
+#[test]
+fn test_custom_rule_is_applied() {
+    let mut simulation = Simulation::new(800.0, 600.0);
+    for name in Rules::NAMES {
+        simulation.set_rule_enabled(name, false);
+    }
+    simulation.add_rule(Wind);
+    simulation.birds.push(Bird::new(Point2::new(400.0, 300.0), Vector2::new(0.0, 0.0)));
+
+    simulation.step(1.0 / 60.0);
+
+    assert_eq!(simulation.birds[0].vel, Vector2::new(0.5, 0.0));
+    assert_eq!(simulation.birds[0].steering.len(), Rules::NAMES.len() + 1);
+    assert_eq!(simulation.birds[0].steering[Rules::NAMES.len()], Vector2::new(0.5, 0.0));
+}
+
+#[test]
+fn test_toggle_rule() {
+    let mut simulation = Simulation::new(800.0, 600.0);
+
+    assert_eq!(simulation.toggle_rule("cohesion"), Some(false));
+    assert!(!simulation.is_rule_enabled("cohesion"));
+    assert_eq!(simulation.toggle_rule("cohesion"), Some(true));
+    assert_eq!(simulation.toggle_rule("gravity"), None);
+}
+
+#[test]
+fn test_rule_order_follows_config() {
+    let rules = Rules {
+        order: vec!["cohesion".to_string(), "alignment".to_string()],
+        alignment: false,
+        ..Rules::default()
+    };
+    let mut simulation = Simulation::new(800.0, 600.0);
+    simulation.set_rules(&rules);
+
+    let names: Vec<&str> = simulation.rules.iter().map(|active| active.rule.name()).collect();
+    assert_eq!(names, vec!["cohesion", "alignment"]);
+    assert!(simulation.is_rule_enabled("cohesion"));
+    assert!(!simulation.is_rule_enabled("alignment"));
+}
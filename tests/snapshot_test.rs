@@ -0,0 +1,130 @@
+use boids::cli::Options;
+use boids::config::Config;
+use boids::entities::{ Motion, Shape };
+use boids::simulation::{ Parameters, Simulation, SpawnPattern, UpdateMode };
+use boids::snapshot::{ Snapshot, SnapshotError };
+use boids::species::{ Interaction, Species };
+use ggez::nalgebra::Point2;
+use structopt::StructOpt;
+
+fn busy_simulation() -> Simulation {
+    let mut simulation = Simulation::with_seed(800.0, 600.0, 11);
+    let mut starling = Species::new("starling");
+    starling.interactions = vec![Interaction::Cohere, Interaction::Avoid];
+    let mut sparrow = Species::new("sparrow");
+    sparrow.parameters = Some(Parameters { max_speed: 2.5, ..Parameters::default() });
+    simulation.set_species(vec![starling, sparrow]);
+    simulation.update_mode = UpdateMode::Sequential;
+    simulation.set_rule_enabled("cohesion", false);
+    simulation.spawn_flock(40, SpawnPattern::Random);
+    simulation.spawn_moving_obstacle(Point2::new(400.0, 300.0), Motion::Orbit { center: Point2::new(400.0, 300.0), radius: 80.0, angular_speed: 0.02, angle: 0.0 });
+    let (pos, shape) = Shape::polygon(&[Point2::new(100.0, 100.0), Point2::new(160.0, 100.0), Point2::new(130.0, 150.0)]);
+    simulation.spawn_shaped_obstacle(pos, shape);
+    simulation.spawn_predator(Point2::new(200.0, 200.0));
+    simulation
+}
+
+fn assert_same_state(a: &Simulation, b: &Simulation) {
+    assert_eq!(a.tick, b.tick);
+    assert_eq!(a.birds.len(), b.birds.len());
+    for (a, b) in a.birds.iter().zip(b.birds.iter()) {
+        assert_eq!(a.id, b.id);
+        assert_eq!(a.pos, b.pos);
+        assert_eq!(a.vel, b.vel);
+    }
+    for (a, b) in a.obstacles.iter().zip(b.obstacles.iter()) {
+        assert_eq!(a.pos, b.pos);
+        assert_eq!(a.motion, b.motion);
+    }
+    for (a, b) in a.predators.iter().zip(b.predators.iter()) {
+        assert_eq!(a.pos, b.pos);
+    }
+}
+
+#[test]
+fn test_restored_simulation_continues_identically() {
+    let mut original = busy_simulation();
+    for _ in 0..10 {
+        original.step(1.0 / 60.0);
+    }
+    let json = original.snapshot().to_json().unwrap();
+    let mut restored = Simulation::from_snapshot(Snapshot::from_json(&json).unwrap()).unwrap();
+
+    assert_eq!(restored.species, original.species);
+    assert_eq!(restored.parameters, original.parameters);
+    assert_eq!(restored.update_mode, UpdateMode::Sequential);
+    assert!(!restored.is_rule_enabled("cohesion"));
+    assert_same_state(&original, &restored);
+
+    for _ in 0..20 {
+        original.step(1.0 / 60.0);
+        restored.step(1.0 / 60.0);
+    }
+    original.spawn_bird(Point2::new(10.0, 10.0));
+    restored.spawn_bird(Point2::new(10.0, 10.0));
+    assert_same_state(&original, &restored);
+}
+
+#[test]
+fn test_save_and_load_file() {
+    let path = std::env::temp_dir().join(format!("boids_snapshot_test_{}.json", std::process::id()));
+    let mut simulation = busy_simulation();
+    simulation.step(1.0 / 60.0);
+    simulation.snapshot().save(&path).unwrap();
+
+    let options = Options::from_iter_safe(vec!["boids".as_ref(), "--resume".as_ref(), path.as_os_str()]).unwrap();
+    let resumed = options.initial_simulation(&Config::default(), None).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_same_state(&simulation, &resumed);
+    assert_eq!(resumed.seed, 11);
+}
+
+#[test]
+fn test_unknown_rule_is_rejected() {
+    let mut snapshot = busy_simulation().snapshot();
+    snapshot.rules[0].name = "gravity".to_string();
+
+    match Simulation::from_snapshot(snapshot) {
+        Err(SnapshotError::UnknownRule(name)) => assert_eq!(name, "gravity"),
+        _ => panic!("expected an unknown rule error")
+    }
+}
+
+#[test]
+fn test_invalid_values_are_rejected() {
+    let invalid = |snapshot: Snapshot| match Simulation::from_snapshot(snapshot) {
+        Err(SnapshotError::Invalid { key, .. }) => key,
+        _ => panic!("expected an invalid value")
+    };
+    let mut snapshot = busy_simulation().snapshot();
+    snapshot.species.truncate(1);
+    snapshot.birds[0].species = 5;
+    assert_eq!(invalid(snapshot), "birds[0].species");
+
+    let mut snapshot = busy_simulation().snapshot();
+    snapshot.width = 0.0;
+    assert_eq!(invalid(snapshot), "width");
+
+    let mut snapshot = busy_simulation().snapshot();
+    snapshot.parameters.max_speed = -1.0;
+    assert_eq!(invalid(snapshot), "parameters.max_speed");
+
+    let mut snapshot = busy_simulation().snapshot();
+    snapshot.species[1].parameters.as_mut().unwrap().topological_neighbours = 0;
+    assert_eq!(invalid(snapshot), "species[1].parameters.topological_neighbours");
+
+    let mut snapshot = busy_simulation().snapshot();
+    snapshot.predators[0].pos.x = f32::NAN;
+    assert_eq!(invalid(snapshot), "predators[0]");
+}
+
+#[test]
+fn test_missing_and_invalid_files() {
+    assert!(Snapshot::load("does/not/exist.json").is_err());
+    assert!(Snapshot::from_json("{\"width\": 800.0}").is_err());
+
+    let options = Options::from_iter_safe(vec!["boids"]).unwrap();
+    assert_eq!(options.snapshot, std::path::PathBuf::from(Snapshot::DEFAULT_PATH));
+    assert_eq!(options.resume, None);
+}
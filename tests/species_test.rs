@@ -0,0 +1,128 @@
+use boids::config::{ Config, ConfigError };
+use boids::entities::Bird;
+use boids::simulation::{ Parameters, Simulation };
+use boids::species::{ Interaction, Species };
+use ggez::nalgebra::{ Point2, Vector2 };
+
+fn two_species(interaction: Interaction) -> Simulation {
+    let mut simulation = Simulation::with_seed(800.0, 600.0, 1);
+    simulation.set_rule_enabled("random_movement", false);
+    let mut starling = Species::new("starling");
+    starling.interactions = vec![Interaction::Cohere, interaction];
+    simulation.set_species(vec![starling, Species::new("sparrow")]);
+    simulation
+}
+
+fn bird(x: f32, vel: Vector2<f32>, species: usize) -> Bird {
+    let mut bird = Bird::new(Point2::new(x, 300.0), vel);
+    bird.species = species;
+    bird
+}
+
+#[test]
+fn test_config_species_override_parameters() {
+    let config = Config::parse(r#"
+        [parameters]
+        max_speed = 3.0
+
+        [[species]]
+        name = "starling"
+
+        [[species]]
+        name = "sparrow"
+        color = [255, 180, 120]
+        parameters = { max_speed = 5.0, obstacle_radius = 80.0 }
+        interactions = { starling = "avoid" }
+    "#).unwrap();
+    let species = config.species();
+
+    assert_eq!(species.len(), 2);
+    assert_eq!(species[0].parameters.unwrap().max_speed, 3.0);
+    assert_eq!(species[1].parameters.unwrap().max_speed, 5.0);
+    assert_eq!(species[1].parameters.unwrap().cohesion_modifier, Parameters::COHESION_MODIFIER);
+    assert_eq!(species[1].parameters.unwrap().obstacle_radius, 80.0);
+    assert_eq!(species[1].color, (255, 180, 120));
+    assert_eq!(species[1].interaction(0), Interaction::Avoid);
+    assert_eq!(species[1].interaction(1), Interaction::Cohere);
+}
+
+#[test]
+fn test_no_species_gives_one_default_species() {
+    let species = Config::parse("").unwrap().species();
+
+    assert_eq!(species, vec![Species::default()]);
+}
+
+#[test]
+fn test_unknown_interaction_species_is_rejected() {
+    let error = Config::parse(r#"
+        [[species]]
+        name = "starling"
+        interactions = { crow = "ignore" }
+    "#).unwrap_err();
+
+    match &error {
+        ConfigError::Invalid { key, .. } => assert_eq!(key, "species.starling.interactions"),
+        _ => panic!("unexpected error {:?}", error),
+    }
+}
+
+#[test]
+fn test_invalid_species_parameter_names_key() {
+    let error = Config::parse(r#"
+        [[species]]
+        name = "starling"
+        parameters = { max_speed = 0.0 }
+    "#).unwrap_err();
+
+    assert!(error.to_string().contains("species.starling.parameters.max_speed"), "{}", error);
+}
+
+#[test]
+fn test_ignored_species_is_not_separated_from() {
+    let mut simulation = two_species(Interaction::Ignore);
+    simulation.birds.push(bird(400.0, Vector2::new(0.0, 0.0), 0));
+    simulation.birds.push(bird(410.0, Vector2::new(0.0, 0.0), 1));
+
+    simulation.step(1.0 / 60.0);
+
+    // the starling ignores the sparrow, the sparrow still avoids the starling
+    assert_eq!(simulation.birds[0].vel.x, 0.0);
+    assert!(simulation.birds[1].vel.x > 0.0);
+}
+
+#[test]
+fn test_avoided_species_is_not_aligned_with() {
+    let mut simulation = two_species(Interaction::Avoid);
+    simulation.set_rule_enabled("separation", false);
+    simulation.birds.push(bird(400.0, Vector2::new(0.0, 0.0), 0));
+    simulation.birds.push(bird(450.0, Vector2::new(0.0, 1.0), 1));
+
+    simulation.step(1.0 / 60.0);
+
+    assert_eq!(simulation.birds[0].vel, Vector2::new(0.0, 0.0));
+}
+
+#[test]
+fn test_species_speed_limit() {
+    let mut simulation = Simulation::with_seed(800.0, 600.0, 1);
+    let mut slow = Species::new("slow");
+    slow.parameters = Some(Parameters { max_speed: 1.0, ..Parameters::default() });
+    simulation.set_species(vec![Species::new("fast"), slow]);
+    simulation.birds.push(bird(100.0, Vector2::new(3.0, 0.0), 0));
+    simulation.birds.push(bird(700.0, Vector2::new(3.0, 0.0), 1));
+
+    simulation.step(1.0 / 60.0);
+
+    assert!(simulation.birds[0].vel.x > 1.0);
+    assert!(simulation.birds[1].vel.x <= 1.0);
+}
+
+#[test]
+fn test_flock_takes_turns_between_species() {
+    let mut simulation = two_species(Interaction::Cohere);
+    simulation.spawn_flock(5, "random".parse().unwrap());
+
+    let species: Vec<usize> = simulation.birds.iter().map(|bird| bird.species).collect();
+    assert_eq!(species, vec![0, 1, 0, 1, 0]);
+}
@@ -74,4 +74,11 @@ fn test_vector_length() {
     let len: f32 = Tools::vector_length(&mut vector);
     
     assert_eq!(len, 7.0710678118654755);
-}
\ No newline at end of file
+}
+#[test]
+fn test_wrapped_offset() {
+    let offset = Tools::wrapped_offset(Point2::new(5.0, 10.0), Point2::new(795.0, 590.0), 800.0, 600.0);
+
+    assert_eq!(offset, Vector2::new(-10.0, -20.0));
+    assert_eq!(Tools::wrapped_offset(Point2::new(100.0, 100.0), Point2::new(300.0, 200.0), 800.0, 600.0), Vector2::new(200.0, 100.0));
+}
@@ -0,0 +1,129 @@
+use boids::cli::{ Options, Recorders };
+use boids::entities::Bird;
+use boids::simulation::{ Simulation, SpawnPattern };
+use boids::snapshot::Snapshot;
+use boids::trajectory::{ TrajectoryFormat, TrajectoryReader, TrajectoryRecorder };
+use ggez::nalgebra::{ Point2, Vector2 };
+use structopt::StructOpt;
+
+fn flock() -> Simulation {
+    let mut simulation = Simulation::with_seed(800.0, 600.0, 3);
+    simulation.spawn_flock(6, SpawnPattern::Random);
+    simulation
+}
+
+#[test]
+fn test_bird_ids_survive_removals() {
+    let mut simulation = flock();
+    let ids: Vec<u64> = simulation.birds.iter().map(|bird| bird.id).collect();
+    assert_eq!(ids, vec![1, 2, 3, 4, 5, 6]);
+
+    simulation.birds[1].is_alive = false;
+    simulation.birds[4].is_alive = false;
+    simulation.step(1.0 / 60.0);
+    let new_id = simulation.add_bird(Bird::new(Point2::new(10.0, 10.0), Vector2::new(1.0, 0.0)));
+
+    let ids: Vec<u64> = simulation.birds.iter().map(|bird| bird.id).collect();
+    assert_eq!(ids, vec![1, 3, 4, 6, 7]);
+    assert_eq!(new_id, 7);
+}
+
+#[test]
+fn test_binary_round_trip() {
+    let mut simulation = flock();
+    let mut recorder = TrajectoryRecorder::new(Vec::new(), TrajectoryFormat::Binary);
+    let mut expected = Vec::new();
+    recorder.record(&simulation).unwrap();
+    for _ in 0..3 {
+        simulation.step(1.0 / 60.0);
+        recorder.record(&simulation).unwrap();
+        expected.push(simulation.birds.clone());
+    }
+
+    let bytes = recorder.finish().unwrap();
+    let mut reader = TrajectoryReader::new(&bytes[..]).unwrap();
+    let names: Vec<&str> = simulation.rules.iter().map(|active| active.rule.name()).collect();
+    assert_eq!(reader.rules(), &names[..]);
+
+    let first = reader.next_frame().unwrap().unwrap();
+    assert_eq!(first.tick, 0);
+    assert!(first.birds.iter().all(|bird| bird.steering.iter().all(|vector| *vector == Vector2::new(0.0, 0.0))));
+
+    for (tick, birds) in expected.iter().enumerate() {
+        let frame = reader.next_frame().unwrap().unwrap();
+        assert_eq!(frame.tick, tick as u64 + 1);
+        assert_eq!(frame.birds.len(), birds.len());
+        for (record, bird) in frame.birds.iter().zip(birds.iter()) {
+            assert_eq!(record.id, bird.id);
+            assert_eq!(record.species, bird.species);
+            assert_eq!(record.pos, bird.pos);
+            assert_eq!(record.vel, bird.vel);
+            assert_eq!(record.steering, bird.steering);
+        }
+    }
+    assert!(reader.next_frame().unwrap().is_none());
+}
+
+#[test]
+fn test_csv_rows() {
+    let mut simulation = flock();
+    simulation.step(1.0 / 60.0);
+    let mut recorder = TrajectoryRecorder::new(Vec::new(), TrajectoryFormat::Csv);
+    recorder.record(&simulation).unwrap();
+    simulation.step(1.0 / 60.0);
+    recorder.record(&simulation).unwrap();
+
+    let csv = String::from_utf8(recorder.finish().unwrap()).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 1 + 2 * 6);
+    assert!(lines[0].starts_with("tick,id,species,x,y,vx,vy,alignment_x,alignment_y,separation_x,separation_y,"));
+    assert_eq!(lines[0].split(',').count(), 7 + 2 * simulation.rules.len());
+    assert!(lines[7].starts_with("2,1,0,"));
+    assert_eq!(lines[12].split(',').count(), lines[0].split(',').count());
+}
+
+#[test]
+fn test_format_from_path() {
+    assert_eq!(TrajectoryFormat::from_path("run.csv"), TrajectoryFormat::Csv);
+    assert_eq!(TrajectoryFormat::from_path("run.CSV"), TrajectoryFormat::Csv);
+    assert_eq!(TrajectoryFormat::from_path("run.traj"), TrajectoryFormat::Binary);
+    assert!(TrajectoryReader::new(&b"tick,id\n"[..]).is_err());
+}
+
+#[test]
+fn test_restarted_recorders_match_the_loaded_rules() {
+    let dir = std::env::temp_dir();
+    let metrics = dir.join(format!("boids_restart_test_{}.csv", std::process::id()));
+    let trajectory = dir.join(format!("boids_restart_test_{}.trj", std::process::id()));
+    let options = Options::from_iter(vec!["boids".as_ref(), "--metrics".as_ref(), metrics.as_os_str(), "--trajectory".as_ref(), trajectory.as_os_str(), "--metrics-interval".as_ref(), "1".as_ref()]);
+    let mut simulation = flock();
+    let mut recorders = Recorders::create(&options).unwrap();
+    recorders.record(&simulation).unwrap();
+
+    // a snapshot of a simulation with only two of the rules, as loaded with F9
+    let mut snapshot: Snapshot = simulation.snapshot();
+    snapshot.rules.truncate(2);
+    simulation = Simulation::from_snapshot(snapshot).unwrap();
+    recorders.restart(&options).unwrap();
+    simulation.step(1.0 / 60.0);
+    recorders.record(&simulation).unwrap();
+    recorders.restart(&Options::from_iter(&["boids"])).unwrap();
+
+    let csv = std::fs::read_to_string(&metrics).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with(",center_y,alignment,separation"));
+    assert!(lines[1].starts_with("1,"));
+    assert_eq!(lines[1].split(',').count(), 13);
+
+    let mut reader = TrajectoryReader::open(&trajectory).unwrap();
+    assert_eq!(reader.rules(), &["alignment".to_string(), "separation".to_string()]);
+    let frame = reader.next_frame().unwrap().unwrap();
+    assert_eq!(frame.tick, 1);
+    assert_eq!(frame.birds.len(), 6);
+    assert!(frame.birds.iter().all(|bird| bird.steering.len() == 2));
+    assert_eq!(reader.next_frame().unwrap(), None);
+
+    std::fs::remove_file(&metrics).unwrap();
+    std::fs::remove_file(&trajectory).unwrap();
+}